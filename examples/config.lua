@@ -7,13 +7,65 @@ local bindings = {}
 for _, super in ipairs(super_keys) do
   bindings[{modifiers = {super, "Shift"}, key = "Escape"}] = "Exit"
   bindings[{modifiers = {super}, key = "q"}] = "Close"
+  -- Spawn takes a shell command, an argv array or a table with
+  -- `shell` or `argv` and optional `env` and `cwd`
   bindings[{modifiers = {super}, key = "Return"}] = {Spawn = term_cmd}
   bindings[{modifiers = {super}, key = "f"}] = "ToggleFullscreen"
+  bindings[{modifiers = {super}, key = "h"}] = {AdjustMasterRatio = -0.05}
+  bindings[{modifiers = {super}, key = "l"}] = {AdjustMasterRatio = 0.05}
+  bindings[{modifiers = {super}, key = "i"}] = {AdjustMasterCount = 1}
+  bindings[{modifiers = {super}, key = "d"}] = {AdjustMasterCount = -1}
+  bindings[{modifiers = {super}, key = "t"}] = {SetLayout = "MasterStack"}
+  bindings[{modifiers = {super}, key = "c"}] = {SetLayout = "Columns"}
+  bindings[{modifiers = {super}, key = "s"}] = {SetLayout = "Dwindle"}
+  bindings[{modifiers = {super}, key = "v"}] = "ToggleSplitDirection"
+  bindings[{modifiers = {super}, key = "m"}] = {SetLayout = "Tree"}
+  bindings[{modifiers = {super, "Ctrl"}, key = "h"}] = {Split = "SplitH"}
+  bindings[{modifiers = {super, "Ctrl"}, key = "v"}] = {Split = "SplitV"}
+  bindings[{modifiers = {super}, key = "w"}] = {SetContainerLayout = "Tabbed"}
+  bindings[{modifiers = {super}, key = "e"}] = {SetContainerLayout = "SplitH"}
+  bindings[{modifiers = {super, "Shift"}, key = "s"}] = {SetContainerLayout = "Stacked"}
+  bindings[{modifiers = {super}, key = "a"}] = "FocusParent"
+  bindings[{modifiers = {super, "Shift"}, key = "a"}] = "FocusChild"
+  bindings[{modifiers = {super}, key = "space"}] = "ToggleFloating"
+  for _, direction in ipairs({"Left", "Right", "Up", "Down"}) do
+    bindings[{modifiers = {super, "Ctrl"}, key = direction}] = {MoveWindow = direction}
+  end
+  for key, direction in pairs({h = "Left", j = "Down", k = "Up", l = "Right"}) do
+    bindings[{modifiers = {super, "Shift"}, key = key}] = {FocusDirection = direction}
+    bindings[{modifiers = {super, "Ctrl", "Shift"}, key = key}] = {SwapDirection = direction}
+  end
+  -- Most recently used windows first
+  bindings[{modifiers = {super}, key = "n"}] = "FocusNext"
+  bindings[{modifiers = {super, "Shift"}, key = "n"}] = "FocusPrev"
+  -- Shows the windows of all workspaces until the modifier is released
+  bindings[{modifiers = {super}, key = "Tab"}] = "SwitcherNext"
+  bindings[{modifiers = {super, "Shift"}, key = "Tab"}] = "SwitcherPrev"
+  bindings[{modifiers = {super}, key = "o"}] = "ToggleOverview"
+  bindings[{modifiers = {super, "Shift"}, key = "minus"}] = "MoveToScratchpad"
+  bindings[{modifiers = {super}, key = "minus"}] = "ToggleScratchpad"
+  bindings[{modifiers = {super}, key = "Left"}] = "FocusColumnLeft"
+  bindings[{modifiers = {super}, key = "Right"}] = "FocusColumnRight"
+  bindings[{modifiers = {super, "Shift"}, key = "Left"}] = "MoveColumnLeft"
+  bindings[{modifiers = {super, "Shift"}, key = "Right"}] = "MoveColumnRight"
+  bindings[{modifiers = {super}, key = "comma"}] = "ConsumeIntoColumn"
+  bindings[{modifiers = {super}, key = "period"}] = "ExpelFromColumn"
+  for key, direction in pairs({comma = "Left", period = "Right"}) do
+    bindings[{modifiers = {super, "Ctrl"}, key = key}] = {FocusOutput = direction}
+    bindings[{modifiers = {super, "Shift"}, key = key}] = {MoveToOutput = direction}
+    bindings[{modifiers = {super, "Ctrl", "Shift"}, key = key}] = {MoveWorkspaceToOutput = direction}
+  end
 
   for i = 1, workspace_count do
     local key = tostring(i)
     bindings[{modifiers = {super}, key = key}] = {SwitchToWorkspace = i}
   end
+  -- `wrap` continues from the other end, `skip_empty` skips workspaces without windows
+  bindings[{modifiers = {super}, key = "bracketright"}] = {NextWorkspace = {wrap = true}}
+  bindings[{modifiers = {super}, key = "bracketleft"}] = {PrevWorkspace = {wrap = true}}
+  bindings[{modifiers = {super, "Shift"}, key = "bracketright"}] = {MoveToNextWorkspace = {}}
+  bindings[{modifiers = {super, "Shift"}, key = "bracketleft"}] = {MoveToPrevWorkspace = {}}
+  bindings[{modifiers = {super}, key = "grave"}] = "LastWorkspace"
 
   for i = 1, workspace_count do
     local key = tostring(i)
@@ -24,6 +76,50 @@ end
 return {
   bindings = bindings,
   workspace_count = workspace_count,
+  -- Names of the first workspaces, which can be targeted by name as well,
+  -- e.g. {SwitchToWorkspace = "web"}
+  workspace_names = {},
+  -- Commands run once at startup
+  autostart = {},
+  -- Commands run at startup and on every configuration reload
+  exec_always = {},
+  tiling = {
+    -- "MasterStack" tiles windows in a master column and a stack column,
+    -- "Columns" places them in columns that scroll horizontally,
+    -- "Dwindle" splits the focused window in half for every new one,
+    -- "Tree" is tiled manually with split, tabbed and stacked containers
+    layout = "MasterStack",
+    master_ratio = 0.5,
+    master_count = 1,
+    -- Fraction of the output width
+    column_width = 0.5,
+  },
+  -- Holding these modifiers, left drag moves and right drag resizes floating windows
+  grabs = {
+    modifiers = {"Super"},
+  },
+  keyboard = {
+    layout = "us",
+    -- options = "ctrl:nocaps",
+    repeat_delay = 180,
+    repeat_rate = 60,
+  },
+  -- Applied to new windows whose app_id and title match, later rules take precedence.
+  -- Strings match exactly, { regex = "..." } matches a regular expression
+  rules = {
+    -- { match = { app_id = "firefox" }, workspace = 2 },
+    -- { match = { title = { regex = "^Picture-in-Picture$" } }, floating = true, size = {640, 360}, position = {20, 20} },
+    -- { match = { app_id = "foot" }, opacity = 0.9, outline_color = {0.8, 0.4, 0.4} },
+  },
+  -- Keyed by output name, outputs without a position are placed to the right of the others
+  outputs = {
+    -- ["headless-1"] = {
+    --   position = {1920, 0},
+    --   scale = 1.5,
+    --   transform = "90",
+    --   mode = { width = 1280, height = 720, refresh = 60 },
+    -- },
+  },
   outline = {
     color = {0.5, 0.5, 0.5},
     focused_color = {0.5, 0.5, 1.0},
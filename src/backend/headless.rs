@@ -0,0 +1,282 @@
+use std::env;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::egl::{EGLContext, EGLDevice, EGLDisplay};
+use smithay::backend::renderer::damage::OutputDamageTracker;
+use smithay::backend::renderer::gles::{GlesRenderbuffer, GlesRenderer, GlesTexture};
+use smithay::backend::renderer::pixman::{PixmanRenderer, PixmanTexture};
+use smithay::backend::renderer::{Offscreen, Renderer};
+use smithay::desktop::Window;
+use smithay::output::{Mode, Output, PhysicalProperties, Subpixel};
+use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
+use smithay::reexports::calloop::LoopHandle;
+use smithay::reexports::pixman::Image;
+use smithay::utils::{Buffer, Physical, Size, Transform};
+use tracing::{error, info};
+
+use super::{Backend, BackendState};
+use crate::render::element::OutputRenderElement;
+use crate::render::shader::OutlineShader;
+use crate::render::{OutlineRenderer, CLEAR_COLOR};
+use crate::state::{CalloopData, State};
+
+const OUTPUTS_ENV: &str = "WELL_HEADLESS_OUTPUTS";
+const DEFAULT_OUTPUT_SIZE: (i32, i32) = (1920, 1080);
+const REFRESH_RATE: i32 = 60_000;
+
+pub trait HeadlessRenderer: OutlineRenderer {
+    type Buffer: Clone;
+
+    fn new() -> Result<Self>;
+    fn create_buffer(&mut self, size: Size<i32, Buffer>) -> Result<Self::Buffer>;
+    fn render_into(
+        &mut self,
+        damage_tracker: &mut OutputDamageTracker,
+        buffer: Self::Buffer,
+        elements: &[OutputRenderElement<Self>],
+    ) -> Result<()>;
+    fn render_offscreen(
+        &mut self,
+        elements: &[OutputRenderElement<Self>],
+        size: Size<i32, Buffer>,
+    ) -> Result<Self::TextureId>;
+}
+
+pub struct Headless<R: HeadlessRenderer> {
+    renderer: R,
+    outputs: Vec<VirtualOutput<R>>,
+}
+
+struct VirtualOutput<R: HeadlessRenderer> {
+    output: Output,
+    damage_tracker: OutputDamageTracker,
+    buffer: R::Buffer,
+    size: Size<i32, Physical>,
+}
+
+impl<R: HeadlessRenderer> Headless<R>
+where
+    BackendState: AsRef<Self> + AsMut<Self>,
+{
+    pub fn init(data: &mut CalloopData) {
+        let headless = data.backend.as_ref::<Self>();
+        for VirtualOutput { output, .. } in headless.outputs.iter() {
+            let _global = output.create_global::<State>(&data.state.display_handle);
+            data.state.add_output(output);
+        }
+    }
+
+    pub fn new(event_loop: LoopHandle<'static, CalloopData>) -> Result<Self> {
+        let mut renderer = R::new()?;
+
+        let sizes = match env::var(OUTPUTS_ENV) {
+            Ok(s) => parse_output_sizes(&s).context("Invalid virtual output sizes")?,
+            Err(_) => vec![DEFAULT_OUTPUT_SIZE.into()],
+        };
+
+        let outputs = sizes
+            .into_iter()
+            .enumerate()
+            .map(|(i, size)| VirtualOutput::new(&mut renderer, format!("headless-{i}"), size))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to create virtual outputs")?;
+
+        let frame_interval = Duration::from_secs_f32(1000. / REFRESH_RATE as f32);
+        let timer = Timer::immediate();
+        event_loop
+            .insert_source(timer, move |_, _, data| {
+                if let Err(err) = data.backend.as_mut::<Self>().render(&mut data.state) {
+                    error!(?err, "Failed to render virtual outputs");
+                }
+                TimeoutAction::ToDuration(frame_interval)
+            })
+            .map_err(|_| anyhow!("Failed to initialize backend source"))?;
+
+        Ok(Self { renderer, outputs })
+    }
+}
+
+impl<R: HeadlessRenderer> Headless<R> {
+    fn render_output(
+        &mut self,
+        state: &mut State,
+        index: usize,
+        focus: Option<&Window>,
+    ) -> Result<()> {
+        self.outputs[index].resize(&mut self.renderer)?;
+        let output = self.outputs[index].output.clone();
+        let elements = state.shell.render_elements(self, &output, focus, &state.config)?;
+        let VirtualOutput { damage_tracker, buffer, .. } = &mut self.outputs[index];
+        self.renderer.render_into(damage_tracker, buffer.clone(), &elements)
+    }
+}
+
+impl<R: HeadlessRenderer> VirtualOutput<R> {
+    fn new(renderer: &mut R, name: String, size: Size<i32, Physical>) -> Result<Self> {
+        let mode = Mode { size, refresh: REFRESH_RATE };
+
+        let output = Output::new(
+            name,
+            PhysicalProperties {
+                size: (0, 0).into(),
+                subpixel: Subpixel::Unknown,
+                make: "Smithay".into(),
+                model: "Headless".into(),
+            },
+        );
+        output.change_current_state(Some(mode), Some(Transform::Normal), None, Some((0, 0).into()));
+        output.set_preferred(mode);
+
+        let damage_tracker = OutputDamageTracker::from_output(&output);
+        let buffer = renderer.create_buffer((size.w, size.h).into())?;
+
+        Ok(Self { output, damage_tracker, buffer, size })
+    }
+
+    // The configured mode can differ from the size the buffer was created with
+    fn resize(&mut self, renderer: &mut R) -> Result<()> {
+        let Some(mode) = self.output.current_mode() else {
+            return Ok(());
+        };
+        if mode.size != self.size {
+            self.buffer = renderer.create_buffer((mode.size.w, mode.size.h).into())?;
+            self.damage_tracker = OutputDamageTracker::from_output(&self.output);
+            self.size = mode.size;
+        }
+        Ok(())
+    }
+}
+
+impl<R: HeadlessRenderer> Backend for Headless<R> {
+    type Renderer = R;
+
+    fn render(&mut self, state: &mut State) -> Result<()> {
+        // An output failing to render does not keep the others from it
+        let focus = state.get_focus();
+        for index in 0..self.outputs.len() {
+            if let Err(err) = self.render_output(state, index, focus.as_ref()) {
+                error!(output = %self.outputs[index].output.name(), ?err, "Failed to render");
+            }
+        }
+
+        state.shell.workspaces.send_frames(state.start_time.elapsed());
+        state.shell.workspaces.refresh();
+
+        Ok(())
+    }
+
+    fn renderer(&mut self) -> &mut R {
+        &mut self.renderer
+    }
+
+    fn render_offscreen(
+        &mut self,
+        elements: &[OutputRenderElement<R>],
+        size: Size<i32, Buffer>,
+    ) -> Result<Option<<R as Renderer>::TextureId>> {
+        if size.w == 0 || size.h == 0 {
+            return Ok(None);
+        }
+        self.renderer.render_offscreen(elements, size).map(Some)
+    }
+}
+
+impl HeadlessRenderer for GlesRenderer {
+    type Buffer = GlesRenderbuffer;
+
+    fn new() -> Result<Self> {
+        let device = EGLDevice::enumerate()?.next().context("No EGL device available")?;
+        info!(?device, "Using EGL device");
+        let display = unsafe { EGLDisplay::new(device)? };
+        let context = EGLContext::new(&display)?;
+        let mut renderer = unsafe { GlesRenderer::new(context)? };
+        OutlineShader::compile(&mut renderer)?;
+        Ok(renderer)
+    }
+
+    fn create_buffer(&mut self, size: Size<i32, Buffer>) -> Result<Self::Buffer> {
+        Ok(Offscreen::<GlesRenderbuffer>::create_buffer(self, Fourcc::Abgr8888, size)?)
+    }
+
+    fn render_into(
+        &mut self,
+        damage_tracker: &mut OutputDamageTracker,
+        buffer: Self::Buffer,
+        elements: &[OutputRenderElement<Self>],
+    ) -> Result<()> {
+        damage_tracker.render_output_with(self, buffer, 0, elements, CLEAR_COLOR)?;
+        Ok(())
+    }
+
+    fn render_offscreen(
+        &mut self,
+        elements: &[OutputRenderElement<Self>],
+        size: Size<i32, Buffer>,
+    ) -> Result<GlesTexture> {
+        let texture = Offscreen::<GlesTexture>::create_buffer(self, Fourcc::Abgr8888, size)?;
+        let mut damage_tracker = OutputDamageTracker::new((size.w, size.h), 1.0, Transform::Normal);
+        damage_tracker.render_output_with(self, texture.clone(), 0, elements, CLEAR_COLOR)?;
+        Ok(texture)
+    }
+}
+
+impl HeadlessRenderer for PixmanRenderer {
+    type Buffer = Image<'static, 'static>;
+
+    fn new() -> Result<Self> {
+        info!("Using pixman renderer");
+        Ok(PixmanRenderer::new()?)
+    }
+
+    fn create_buffer(&mut self, size: Size<i32, Buffer>) -> Result<Self::Buffer> {
+        Ok(Offscreen::<Image<'static, 'static>>::create_buffer(self, Fourcc::Argb8888, size)?)
+    }
+
+    fn render_into(
+        &mut self,
+        damage_tracker: &mut OutputDamageTracker,
+        buffer: Self::Buffer,
+        elements: &[OutputRenderElement<Self>],
+    ) -> Result<()> {
+        damage_tracker.render_output_with(self, buffer, 0, elements, CLEAR_COLOR)?;
+        Ok(())
+    }
+
+    fn render_offscreen(
+        &mut self,
+        elements: &[OutputRenderElement<Self>],
+        size: Size<i32, Buffer>,
+    ) -> Result<PixmanTexture> {
+        let image = HeadlessRenderer::create_buffer(self, size)?;
+        let mut damage_tracker = OutputDamageTracker::new((size.w, size.h), 1.0, Transform::Normal);
+        damage_tracker.render_output_with(self, image.clone(), 0, elements, CLEAR_COLOR)?;
+        Ok(PixmanTexture::from(image))
+    }
+}
+
+fn parse_output_sizes(s: &str) -> Result<Vec<Size<i32, Physical>>> {
+    s.split(',')
+        .map(|size| {
+            let (w, h) = size.trim().split_once('x').context("Expected WIDTHxHEIGHT")?;
+            let (w, h) = (w.parse::<i32>()?, h.parse::<i32>()?);
+            anyhow::ensure!(w > 0 && h > 0, "Output size should be > 0");
+            Ok((w, h).into())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_sizes() {
+        let sizes = parse_output_sizes("1920x1080, 1280x720").unwrap();
+        assert_eq!(sizes, vec![(1920, 1080).into(), (1280, 720).into()]);
+        assert!(parse_output_sizes("1920").is_err());
+        assert!(parse_output_sizes("0x1080").is_err());
+        assert!(parse_output_sizes("1920x1080,").is_err());
+    }
+}
@@ -1,44 +1,114 @@
 use std::env;
+use std::str::FromStr;
 
-use anyhow::Result;
-use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
+use anyhow::{bail, Context, Result};
+use smithay::backend::renderer::gles::GlesRenderer;
+use smithay::backend::renderer::pixman::PixmanRenderer;
+use smithay::backend::renderer::Renderer;
 use smithay::reexports::calloop::LoopHandle;
 use smithay::utils::{Buffer, Size};
+use tracing::{info, warn};
 
+use self::headless::Headless;
 use self::winit::Winit;
+use self::x11::X11;
 use crate::render::element::OutputRenderElement;
+use crate::render::OutlineRenderer;
 use crate::state::{CalloopData, State};
 
+mod headless;
 mod winit;
+mod x11;
+
+const BACKEND_ENV: &str = "WELL_BACKEND";
+
+// Textures of any renderer can be kept in window user data
+pub trait Backend: 'static {
+    type Renderer: OutlineRenderer;
 
-pub trait Backend {
     fn render(&mut self, state: &mut State) -> Result<()>;
-    fn renderer(&mut self) -> &mut GlesRenderer;
+    fn renderer(&mut self) -> &mut Self::Renderer;
     fn render_offscreen(
         &mut self,
-        elements: &[OutputRenderElement],
+        elements: &[OutputRenderElement<Self::Renderer>],
         size: Size<i32, Buffer>,
-    ) -> Result<Option<GlesTexture>>;
+    ) -> Result<Option<<Self::Renderer as Renderer>::TextureId>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    Winit,
+    X11,
+    Headless,
+}
+
+impl BackendKind {
+    fn detect() -> Result<Self> {
+        if let Ok(kind) = env::var(BACKEND_ENV) {
+            return kind.parse().with_context(|| format!("Invalid {BACKEND_ENV}"));
+        }
+        if env::var_os("WAYLAND_DISPLAY").is_some() || env::var_os("DISPLAY").is_some() {
+            Ok(Self::Winit)
+        } else {
+            Ok(Self::Headless)
+        }
+    }
+}
+
+impl FromStr for BackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "winit" => Ok(Self::Winit),
+            "x11" => Ok(Self::X11),
+            "headless" => Ok(Self::Headless),
+            _ => bail!("Unknown backend '{s}', expected one of: winit, x11, headless"),
+        }
+    }
 }
 
 pub enum BackendState {
     Winit(Winit),
+    X11(X11),
+    Headless(Headless<GlesRenderer>),
+    Pixman(Headless<PixmanRenderer>),
 }
 
 impl BackendState {
-    pub fn new(event_loop: LoopHandle<'static, CalloopData>) -> Self {
-        if env::var_os("WAYLAND_DISPLAY").is_some() || env::var_os("DISPLAY").is_some() {
-            Self::Winit(Winit::new(event_loop))
-        } else {
-            panic!("Standalone mode is not supported");
+    pub fn new(
+        kind: Option<BackendKind>,
+        event_loop: LoopHandle<'static, CalloopData>,
+    ) -> Result<Self> {
+        let kind = match kind {
+            Some(kind) => kind,
+            None => BackendKind::detect()?,
+        };
+        info!(?kind, "Initializing backend");
+        match kind {
+            BackendKind::Winit => Ok(Self::Winit(Winit::new(event_loop)?)),
+            BackendKind::X11 => Ok(Self::X11(X11::new(event_loop)?)),
+            BackendKind::Headless
+                if env::var("WELL_RENDERER").is_ok_and(|renderer| renderer == "pixman") =>
+            {
+                Ok(Self::Pixman(Headless::new(event_loop)?))
+            }
+            BackendKind::Headless => match Headless::new(event_loop.clone()) {
+                Ok(headless) => Ok(Self::Headless(headless)),
+                Err(err) => {
+                    warn!(?err, "Failed to initialize GL renderer, falling back to pixman");
+                    Ok(Self::Pixman(Headless::new(event_loop)?))
+                }
+            },
         }
     }
 
     pub fn init(data: &mut CalloopData) {
-        if env::var_os("WAYLAND_DISPLAY").is_some() || env::var_os("DISPLAY").is_some() {
-            Winit::init(data);
-        } else {
-            panic!("Standalone mode is not supported");
+        match data.backend {
+            Self::Winit(_) => Winit::init(data),
+            Self::X11(_) => X11::init(data),
+            Self::Headless(_) => Headless::<GlesRenderer>::init(data),
+            Self::Pixman(_) => Headless::<PixmanRenderer>::init(data),
         }
     }
 
@@ -57,16 +127,29 @@ impl BackendState {
     }
 }
 
-impl AsRef<Winit> for BackendState {
-    fn as_ref(&self) -> &Winit {
-        let Self::Winit(b) = self;
-        b
-    }
-}
+macro_rules! impl_backend_variant {
+    ($variant:ident, $backend:ty) => {
+        impl AsRef<$backend> for BackendState {
+            fn as_ref(&self) -> &$backend {
+                let Self::$variant(b) = self else {
+                    unreachable!(concat!("Not a ", stringify!($variant), " backend"))
+                };
+                b
+            }
+        }
 
-impl AsMut<Winit> for BackendState {
-    fn as_mut(&mut self) -> &mut Winit {
-        let Self::Winit(b) = self;
-        b
-    }
+        impl AsMut<$backend> for BackendState {
+            fn as_mut(&mut self) -> &mut $backend {
+                let Self::$variant(b) = self else {
+                    unreachable!(concat!("Not a ", stringify!($variant), " backend"))
+                };
+                b
+            }
+        }
+    };
 }
+
+impl_backend_variant!(Winit, Winit);
+impl_backend_variant!(X11, X11);
+impl_backend_variant!(Headless, Headless<GlesRenderer>);
+impl_backend_variant!(Pixman, Headless<PixmanRenderer>);
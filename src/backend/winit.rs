@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use smithay::backend::allocator::Fourcc;
 use smithay::backend::renderer::damage::{OutputDamageTracker, RenderOutputResult};
 use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
@@ -9,6 +9,7 @@ use smithay::backend::winit::{self, WinitEvent, WinitEventLoop, WinitGraphicsBac
 use smithay::output::{Mode, Output, PhysicalProperties, Subpixel};
 use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
 use smithay::reexports::calloop::LoopHandle;
+use smithay::reexports::winit::dpi::PhysicalSize;
 use smithay::reexports::winit::platform::pump_events::PumpStatus;
 use smithay::utils::{Buffer, Size, Transform};
 
@@ -18,6 +19,9 @@ use crate::render::shader::OutlineShader;
 use crate::render::CLEAR_COLOR;
 use crate::state::{CalloopData, State};
 
+// Used until the output has a mode
+const REFRESH_RATE: i32 = 60_000;
+
 pub struct Winit {
     backend: WinitGraphicsBackend<GlesRenderer>,
     damage_tracker: OutputDamageTracker,
@@ -28,14 +32,14 @@ impl Winit {
     pub fn init(data: &mut CalloopData) {
         let output = &data.backend.as_ref::<Self>().output;
         let _global = output.create_global::<State>(&data.state.display_handle);
-        data.state.shell.workspaces.map_output(output);
+        data.state.add_output(output);
     }
 
-    pub fn new(event_loop: LoopHandle<'static, CalloopData>) -> Self {
-        let (mut backend, mut winit) =
-            winit::init::<GlesRenderer>().expect("Failed to initialize backend");
+    pub fn new(event_loop: LoopHandle<'static, CalloopData>) -> Result<Self> {
+        let (mut backend, mut winit) = winit::init::<GlesRenderer>()
+            .map_err(|err| anyhow!("Failed to initialize winit backend: {err}"))?;
 
-        let mode = Mode { size: backend.window_size(), refresh: 60_000 };
+        let mode = Mode { size: backend.window_size(), refresh: REFRESH_RATE };
 
         let output = Output::new(
             "winit".to_string(),
@@ -46,6 +50,7 @@ impl Winit {
                 model: "Winit".into(),
             },
         );
+        // GL renders upside down into the window, a configured transform is applied on top
         output.change_current_state(
             Some(mode),
             Some(Transform::Flipped180),
@@ -56,26 +61,29 @@ impl Winit {
 
         let damage_tracker = OutputDamageTracker::from_output(&output);
 
-        OutlineShader::compile(backend.renderer());
+        OutlineShader::compile(backend.renderer()).context("Failed to compile outline shader")?;
 
         let timer = Timer::immediate();
         event_loop
             .insert_source(timer, move |_, _, data| {
-                data.backend.as_mut::<Self>().dispatch(&mut data.state, &mut winit);
-                TimeoutAction::ToDuration(Duration::from_secs_f32(1. / 60.))
+                let winit_backend = data.backend.as_mut::<Self>();
+                winit_backend.dispatch(&mut data.state, &mut winit);
+                TimeoutAction::ToDuration(Duration::from_secs_f32(
+                    1000. / winit_backend.refresh() as f32,
+                ))
             })
-            .map_err(|_| anyhow!("Failed to initialize backend source"))
-            .unwrap();
+            .map_err(|_| anyhow!("Failed to initialize backend source"))?;
 
-        Self { backend, damage_tracker, output }
+        Ok(Self { backend, damage_tracker, output })
     }
 
     pub fn dispatch(&mut self, state: &mut State, winit: &mut WinitEventLoop) {
         let dispatcher = winit.dispatch_new_events(|event| match event {
             WinitEvent::Resized { size, .. } => {
-                state.shell.workspaces.change_output_mode(Mode { size, refresh: 60_000 })
+                let mode = Mode { size, refresh: self.refresh() };
+                state.shell.workspaces.change_output_mode(&self.output, mode);
             }
-            WinitEvent::Input(event) => state.handle_input(event),
+            WinitEvent::Input(event) => state.handle_input(event, &self.output),
             WinitEvent::Redraw => self.render(state).unwrap(),
             _ => (),
         });
@@ -83,13 +91,28 @@ impl Winit {
             state.is_running = false;
         }
     }
+
+    fn refresh(&self) -> i32 {
+        self.output.current_mode().map_or(REFRESH_RATE, |mode| mode.refresh)
+    }
 }
 
 impl Backend for Winit {
+    type Renderer = GlesRenderer;
+
     fn render(&mut self, state: &mut State) -> Result<()> {
+        // A configured mode resizes the window, which then reports the new size
+        let size = self.backend.window_size();
+        if let Some(mode) = self.output.current_mode().filter(|mode| mode.size != size) {
+            let _ = self
+                .backend
+                .window()
+                .request_inner_size(PhysicalSize::new(mode.size.w, mode.size.h));
+        }
+
         let focus = state.get_focus();
-        let elements =
-            state.shell.workspaces.render_elements(self, focus.as_ref(), &state.config)?;
+        let output = self.output.clone();
+        let elements = state.shell.render_elements(self, &output, focus.as_ref(), &state.config)?;
         let backend = &mut self.backend;
         backend.bind()?;
         let age = backend.buffer_age().unwrap_or_default();
@@ -113,7 +136,7 @@ impl Backend for Winit {
 
     fn render_offscreen(
         &mut self,
-        elements: &[OutputRenderElement],
+        elements: &[OutputRenderElement<GlesRenderer>],
         size: Size<i32, Buffer>,
     ) -> Result<Option<GlesTexture>> {
         if size.w == 0 || size.h == 0 {
@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Context, Result};
+use smithay::backend::allocator::dmabuf::DmabufAllocator;
+use smithay::backend::allocator::gbm::{GbmAllocator, GbmBufferFlags, GbmDevice};
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::egl::{EGLContext, EGLDisplay};
+use smithay::backend::renderer::damage::OutputDamageTracker;
+use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
+use smithay::backend::renderer::{Bind, Offscreen};
+use smithay::backend::x11::{Window, WindowBuilder, X11Backend, X11Event, X11Surface};
+use smithay::output::{Mode, Output, PhysicalProperties, Subpixel};
+use smithay::reexports::calloop::LoopHandle;
+use smithay::utils::{Buffer, DeviceFd, Logical, Size, Transform};
+use tracing::error;
+
+use super::Backend;
+use crate::render::element::OutputRenderElement;
+use crate::render::shader::OutlineShader;
+use crate::render::CLEAR_COLOR;
+use crate::state::{CalloopData, State};
+
+const REFRESH_RATE: i32 = 60_000;
+
+pub struct X11 {
+    renderer: GlesRenderer,
+    surface: X11Surface,
+    window: Window,
+    damage_tracker: OutputDamageTracker,
+    output: Output,
+}
+
+impl X11 {
+    pub fn init(data: &mut CalloopData) {
+        let output = &data.backend.as_ref::<Self>().output;
+        let _global = output.create_global::<State>(&data.state.display_handle);
+        data.state.add_output(output);
+    }
+
+    pub fn new(event_loop: LoopHandle<'static, CalloopData>) -> Result<Self> {
+        let backend = X11Backend::new().context("Failed to connect to the X server")?;
+        let handle = backend.handle();
+
+        // Buffers are allocated on the device the X server renders with
+        let (_, fd) = handle.drm_node().context("Failed to get the DRM node of the X server")?;
+        let device = GbmDevice::new(DeviceFd::from(fd))?;
+        let display = unsafe { EGLDisplay::new(device.clone())? };
+        let context = EGLContext::new(&display)?;
+        let modifiers = context
+            .dmabuf_render_formats()
+            .iter()
+            .map(|format| format.modifier)
+            .collect::<HashSet<_>>();
+
+        let window = WindowBuilder::new().title("well").build(&handle)?;
+        let allocator = DmabufAllocator(GbmAllocator::new(device, GbmBufferFlags::RENDERING));
+        let surface = handle.create_surface(&window, allocator, modifiers.into_iter())?;
+
+        let mut renderer = unsafe { GlesRenderer::new(context)? };
+        OutlineShader::compile(&mut renderer).context("Failed to compile outline shader")?;
+
+        let output = Output::new(
+            "x11".to_string(),
+            PhysicalProperties {
+                size: (0, 0).into(),
+                subpixel: Subpixel::Unknown,
+                make: "Smithay".into(),
+                model: "X11".into(),
+            },
+        );
+        let mode = window_mode(window.size());
+        output.change_current_state(Some(mode), Some(Transform::Normal), None, Some((0, 0).into()));
+        output.set_preferred(mode);
+
+        let damage_tracker = OutputDamageTracker::from_output(&output);
+
+        // Every presented frame asks for the next one
+        event_loop
+            .insert_source(backend, |event, _, data| {
+                let x11 = data.backend.as_mut::<Self>();
+                match event {
+                    X11Event::CloseRequested { .. } => data.state.is_running = false,
+                    X11Event::Resized { new_size, .. } => {
+                        let mode = window_mode(new_size);
+                        data.state.shell.workspaces.change_output_mode(&x11.output, mode);
+                    }
+                    X11Event::Refresh { .. } | X11Event::PresentCompleted { .. } => {
+                        if let Err(err) = x11.render(&mut data.state) {
+                            error!(?err, "Failed to render");
+                        }
+                    }
+                    X11Event::Input(event) => data.state.handle_input(event, &x11.output),
+                    _ => (),
+                }
+            })
+            .map_err(|_| anyhow!("Failed to initialize backend source"))?;
+
+        Ok(Self { renderer, surface, window, damage_tracker, output })
+    }
+}
+
+impl Backend for X11 {
+    type Renderer = GlesRenderer;
+
+    fn render(&mut self, state: &mut State) -> Result<()> {
+        // The window keeps the size the X server gives it, a configured mode cannot change it
+        let mode = window_mode(self.window.size());
+        if self.output.current_mode().is_some_and(|current| current.size != mode.size) {
+            state.shell.workspaces.change_output_mode(&self.output, mode);
+        }
+
+        let focus = state.get_focus();
+        let output = self.output.clone();
+        let elements = state.shell.render_elements(self, &output, focus.as_ref(), &state.config)?;
+        let (buffer, age) = self.surface.buffer()?;
+        self.renderer.bind(buffer)?;
+        let res = self.damage_tracker.render_output(
+            &mut self.renderer,
+            age.into(),
+            &elements,
+            CLEAR_COLOR,
+        );
+        if let Err(err) = res {
+            self.surface.reset_buffers();
+            return Err(err.into());
+        }
+        self.surface.submit()?;
+
+        state.shell.workspaces.send_frames(state.start_time.elapsed());
+        state.shell.workspaces.refresh();
+
+        Ok(())
+    }
+
+    fn renderer(&mut self) -> &mut GlesRenderer {
+        &mut self.renderer
+    }
+
+    fn render_offscreen(
+        &mut self,
+        elements: &[OutputRenderElement<GlesRenderer>],
+        size: Size<i32, Buffer>,
+    ) -> Result<Option<GlesTexture>> {
+        if size.w == 0 || size.h == 0 {
+            return Ok(None);
+        }
+        let renderer = &mut self.renderer;
+        let texture = Offscreen::<GlesTexture>::create_buffer(renderer, Fourcc::Abgr8888, size)?;
+        let mut damage_tracker = OutputDamageTracker::new((size.w, size.h), 1.0, Transform::Normal);
+        damage_tracker.render_output_with(renderer, texture.clone(), 0, elements, CLEAR_COLOR)?;
+        Ok(Some(texture))
+    }
+}
+
+fn window_mode(size: Size<u16, Logical>) -> Mode {
+    Mode { size: (size.w as i32, size.h as i32).into(), refresh: REFRESH_RATE }
+}
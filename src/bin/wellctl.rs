@@ -0,0 +1,194 @@
+use std::env;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use well::config::{Action, Config};
+use well::ipc::client::Client;
+use well::ipc::message::{OutputInfo, Request, Response, WindowInfo, WorkspaceInfo};
+use well::PKG_VERSION;
+
+const USAGE: &str = "Usage: wellctl [OPTIONS] <COMMAND>
+
+Commands:
+  action <ACTION> [ARGS]...  Run an action, e.g. `wellctl action SwitchToWorkspace 2`
+  workspaces                 List workspaces
+  windows                    List windows
+  outputs                    List outputs
+  subscribe                  Print events as they happen
+  check-config [PATH]        Validate a configuration file
+
+Options:
+  -j, --json           Print replies as JSON
+  -s, --socket <PATH>  Path to the IPC socket, defaults to $WELL_SOCKET
+  -h, --help           Print help
+  -V, --version        Print version";
+
+#[derive(Default)]
+struct Args {
+    json: bool,
+    socket: Option<PathBuf>,
+    command: Option<String>,
+    args: Vec<String>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if parsed.command.as_deref() == Some("action") {
+                parsed.args.push(arg);
+                continue;
+            }
+            match arg.as_str() {
+                "-j" | "--json" => parsed.json = true,
+                "-s" | "--socket" => {
+                    parsed.socket =
+                        Some(args.next().context("Missing value for '--socket'")?.into())
+                }
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                "-V" | "--version" => {
+                    println!("wellctl {PKG_VERSION}");
+                    std::process::exit(0);
+                }
+                _ if arg.starts_with('-') => bail!("Unexpected argument '{arg}'\n\n{USAGE}"),
+                _ if parsed.command.is_none() => parsed.command = Some(arg),
+                _ => parsed.args.push(arg),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse()?;
+    let Some(command) = args.command.as_deref() else {
+        bail!("Missing command\n\n{USAGE}");
+    };
+
+    let request = match command {
+        "action" => Request::Action(parse_action(&args.args)?),
+        "workspaces" => Request::Workspaces,
+        "windows" => Request::Windows,
+        "outputs" => Request::Outputs,
+        "subscribe" => return subscribe(&args),
+        "check-config" => return check_config(&args),
+        _ => bail!("Unknown command '{command}'\n\n{USAGE}"),
+    };
+
+    let response = Client::connect(args.socket.as_deref())?.request(&request)?;
+    if args.json {
+        print_json(&response)?;
+        return Ok(());
+    }
+    match response {
+        Response::Handled | Response::Subscribed => (),
+        Response::Workspaces(workspaces) => workspaces.iter().for_each(print_workspace),
+        Response::Windows(windows) => windows.iter().for_each(print_window),
+        Response::Outputs(outputs) => outputs.iter().for_each(print_output),
+    }
+    Ok(())
+}
+
+fn parse_action(args: &[String]) -> Result<Action> {
+    let Some((name, args)) = args.split_first() else {
+        bail!("Missing action\n\n{USAGE}");
+    };
+    let parse = |arg: &String| serde_json::from_str(arg).unwrap_or(Value::String(arg.clone()));
+    let with_arg = |arg: Value| Value::Object([(name.clone(), arg)].into_iter().collect());
+    let value = match args {
+        [] => Value::String(name.clone()),
+        [arg] => with_arg(parse(arg)),
+        args => with_arg(Value::Array(args.iter().map(parse).collect())),
+    };
+    // Actions with options, like NextWorkspace, run with their defaults without arguments
+    let action = match serde_json::from_value(value) {
+        Err(_) if args.is_empty() => serde_json::from_value(with_arg(Value::Object(Map::new()))),
+        action => action,
+    };
+    action.with_context(|| format!("Invalid action '{name}'"))
+}
+
+fn subscribe(args: &Args) -> Result<()> {
+    for event in Client::connect(args.socket.as_deref())?.subscribe()? {
+        let event = event?;
+        if args.json {
+            print_json(&event)?;
+        } else {
+            println!("{event:?}");
+        }
+    }
+    Ok(())
+}
+
+fn check_config(args: &Args) -> Result<()> {
+    let path = match args.args.first() {
+        Some(path) => PathBuf::from(path),
+        None => Config::find().context("No configuration file found")?,
+    };
+    match Config::try_from(path.as_path()) {
+        Ok(_) => {
+            println!("Configuration file {path:?} is valid");
+            Ok(())
+        }
+        Err(err) => bail!("Configuration file {path:?} is invalid: {err}"),
+    }
+}
+
+fn print_json(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string(value)?);
+    Ok(())
+}
+
+fn print_workspace(workspace: &WorkspaceInfo) {
+    let name = workspace.name.as_ref().map(|name| format!(" \"{name}\"")).unwrap_or_default();
+    let current = if workspace.is_current { " (current)" } else { "" };
+    println!(
+        "Workspace {}{name}{current}: {} windows, {:?} layout",
+        workspace.index, workspace.window_count, workspace.layout
+    );
+}
+
+fn print_window(window: &WindowInfo) {
+    let mut flags = vec![];
+    if window.is_focused {
+        flags.push("focused");
+    }
+    if window.is_fullscreen {
+        flags.push("fullscreen");
+    }
+    if window.is_floating {
+        flags.push("floating");
+    }
+    let geometry = window.geometry;
+    println!(
+        "[{}] {} \"{}\" {}x{}+{}+{} {}",
+        window.workspace,
+        window.app_id.as_deref().unwrap_or("-"),
+        window.title.as_deref().unwrap_or_default(),
+        geometry.width,
+        geometry.height,
+        geometry.x,
+        geometry.y,
+        flags.join(" "),
+    );
+}
+
+fn print_output(output: &OutputInfo) {
+    let mode = output
+        .mode
+        .map(|mode| format!("{}x{}@{:.3}Hz", mode.width, mode.height, mode.refresh as f64 / 1000.))
+        .unwrap_or_else(|| "no mode".to_owned());
+    let (x, y) = output.location;
+    let workspace = output.workspace.map(|n| format!(", workspace {n}")).unwrap_or_default();
+    let focused = if output.is_focused { " (focused)" } else { "" };
+    println!(
+        "{}{focused}: {mode}, scale {}, transform {}, at {x},{y}{workspace}",
+        output.name, output.scale, output.transform
+    );
+}
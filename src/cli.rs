@@ -0,0 +1,100 @@
+use std::env;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+
+use crate::backend::BackendKind;
+use crate::{PKG_NAME, PKG_VERSION};
+
+#[derive(Debug, Default)]
+pub struct Cli {
+    pub backend: Option<BackendKind>,
+    pub config: Option<PathBuf>,
+    pub check_config: bool,
+    pub socket: Option<String>,
+    pub session: Option<String>,
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    Version,
+}
+
+impl Cli {
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(env::args().skip(1))
+    }
+
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_owned(), Some(value.to_owned())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .with_context(|| format!("Missing value for '{flag}'"))
+            };
+            match flag.as_str() {
+                "-b" | "--backend" => cli.backend = Some(value()?.parse()?),
+                "-c" | "--config" => cli.config = Some(value()?.into()),
+                "--check-config" => cli.check_config = true,
+                "-s" | "--socket" => cli.socket = Some(value()?),
+                "-e" | "--session" => cli.session = Some(value()?),
+                "-h" | "--help" => cli.command = Some(Command::Help),
+                "-V" | "--version" => cli.command = Some(Command::Version),
+                _ => bail!("Unexpected argument '{flag}', see '{PKG_NAME} --help'"),
+            }
+        }
+        Ok(cli)
+    }
+
+    pub fn version() -> String {
+        format!("{PKG_NAME} {PKG_VERSION}")
+    }
+
+    pub fn usage() -> String {
+        format!(
+            "Usage: {PKG_NAME} [OPTIONS]
+
+Options:
+  -b, --backend <BACKEND>  Backend to use: winit, x11 or headless
+  -c, --config <PATH>      Path to the configuration file
+      --check-config       Validate the configuration file and exit
+  -s, --socket <NAME>      Name of the Wayland socket to listen on
+  -e, --session <COMMAND>  Command to run as the session, exits when it does
+  -h, --help               Print help
+  -V, --version            Print version"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli> {
+        Cli::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_args() {
+        let cli =
+            parse(&["--backend", "headless", "--config=well.lua", "-s", "wayland-5"]).unwrap();
+        assert_eq!(cli.backend, Some(BackendKind::Headless));
+        assert_eq!(cli.config, Some(PathBuf::from("well.lua")));
+        assert_eq!(cli.socket.as_deref(), Some("wayland-5"));
+        assert!(!cli.check_config);
+
+        assert!(parse(&["--socket"]).is_err());
+        assert_eq!(parse(&["--backend", "x11"]).unwrap().backend, Some(BackendKind::X11));
+        assert!(parse(&["--backend", "drm"]).is_err());
+        assert!(parse(&["--unknown"]).is_err());
+    }
+}
@@ -5,16 +5,20 @@ use std::str::FromStr;
 
 use anyhow::Result;
 use mlua::{Error as LuaError, Lua, LuaSerdeExt};
-use serde::Deserialize;
-use smithay::input::keyboard::{keysyms as Keysyms, xkb, Keysym, ModifiersState};
+use serde::{Deserialize, Serialize};
+use smithay::input::keyboard::{keysyms as Keysyms, xkb, Keysym, ModifiersState, XkbConfig};
+use smithay::output::Mode;
 use smithay::reexports::calloop::channel::Event as ChannelEvent;
 use smithay::reexports::calloop::{self, LoopHandle};
+use smithay::utils::Transform;
 use tracing::{debug, error, info, warn};
 
+pub use self::rules::{Rule, WindowRule};
 use self::watcher::Watcher;
 use crate::state::CalloopData;
 use crate::PKG_NAME;
 
+mod rules;
 mod watcher;
 
 const DEFAULT_CONFIG: &str = include_str!("../../examples/config.lua");
@@ -28,8 +32,26 @@ pub struct Config {
     pub bindings: Bindings,
     #[serde(default = "default_workspace_count")]
     pub workspace_count: usize,
+    // Names of the first workspaces, all of them are kept even when empty
+    #[serde(default)]
+    pub workspace_names: Vec<String>,
     #[serde(alias = "border")]
     pub outline: Outline,
+    #[serde(default)]
+    pub tiling: Tiling,
+    #[serde(default)]
+    pub grabs: Grabs,
+    #[serde(default)]
+    pub keyboard: Keyboard,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    // Keyed by output name
+    #[serde(default)]
+    pub outputs: HashMap<String, OutputConfig>,
+    #[serde(default, alias = "exec_once")]
+    pub autostart: Vec<Spawn>,
+    #[serde(default)]
+    pub exec_always: Vec<Spawn>,
 }
 
 impl Default for Config {
@@ -43,21 +65,18 @@ impl Default for Config {
 impl mlua::UserData for Config {}
 
 impl Config {
-    pub fn new() -> Result<Self> {
-        let config = if let Some(path) = {
-            let xdg = xdg::BaseDirectories::new().ok();
-            xdg.and_then(|base| {
-                base.find_config_file(format!("{PKG_NAME}/config.lua"))
-                    .or_else(|| base.find_config_file(format!("{PKG_NAME}.lua")))
-            })
-        } {
+    pub fn new(path: Option<&Path>) -> Result<Self> {
+        let config = if let Some(path) = path {
+            Config::try_from(path)
+                .map_err(|err| anyhow::anyhow!("Failed to load configuration file: {err}"))?
+        } else if let Some(path) = Self::find() {
             match Config::try_from(path.as_path()) {
                 Ok(cfg) => cfg,
                 Err(Error::Io(err)) => {
                     error!(?err, "Failed to load configuration file");
                     Self::default()
                 }
-                Err(Error::Lua(err)) => {
+                Err(err @ (Error::Lua(_) | Error::Invalid(_))) => {
                     anyhow::bail!("Failed to parse configuration file: {err}");
                 }
             }
@@ -80,10 +99,73 @@ impl Config {
         Ok(config)
     }
 
-    fn reload(&mut self) {
+    pub fn find() -> Option<PathBuf> {
+        let xdg = xdg::BaseDirectories::new().ok();
+        xdg.and_then(|base| {
+            base.find_config_file(format!("{PKG_NAME}/config.lua"))
+                .or_else(|| base.find_config_file(format!("{PKG_NAME}.lua")))
+        })
+    }
+
+    // Loads the file again, leaving the current configuration to the caller to replace
+    pub fn reload(&self) -> Result<Self, Error> {
         debug!("Reloading configuration");
-        let config = Self::try_from(self.path.as_path()).unwrap();
-        *self = config;
+        Self::try_from(self.path.as_path())
+    }
+
+    // Catches what deserializing can't, before anything is applied
+    fn validate(&self) -> Result<(), Error> {
+        if self.workspace_count == 0 {
+            return Err(Error::Invalid("workspace_count should be > 0".to_string()));
+        }
+        let tiling = &self.tiling;
+        if !(0.1..=0.9).contains(&tiling.master_ratio) {
+            return Err(Error::Invalid("tiling.master_ratio should be in 0.1..=0.9".to_string()));
+        }
+        if !(0.1..=1.0).contains(&tiling.column_width) {
+            return Err(Error::Invalid("tiling.column_width should be in 0.1..=1.0".to_string()));
+        }
+        for (name, output) in self.outputs.iter() {
+            if output.scale.is_some_and(|scale| !scale.is_finite() || scale <= 0.0) {
+                return Err(Error::Invalid(format!("Output {name}: scale should be > 0")));
+            }
+            let is_invalid = |mode: OutputMode| {
+                mode.width <= 0
+                    || mode.height <= 0
+                    || !mode.refresh.is_finite()
+                    || mode.refresh <= 0.0
+            };
+            if let Some(mode) = output.mode.filter(|&mode| is_invalid(mode)) {
+                return Err(Error::Invalid(format!("Output {name}: invalid mode {mode:?}")));
+            }
+        }
+        // Tiled windows get their geometry from the layout
+        for (n, rule) in self.rules.iter().enumerate() {
+            let is_placed = rule.size.is_some() || rule.position.is_some();
+            if is_placed && rule.floating != Some(true) {
+                let msg = format!("Rule {}: size and position need floating = true", n + 1);
+                return Err(Error::Invalid(msg));
+            }
+        }
+        let keyboard = &self.keyboard;
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_names(
+            &context,
+            "",
+            "",
+            keyboard.layout.as_str(),
+            keyboard.variant.as_str(),
+            keyboard.options.clone(),
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        );
+        if keymap.is_none() {
+            return Err(Error::Invalid(format!("Invalid keyboard layout: {keyboard:?}")));
+        }
+        Ok(())
+    }
+
+    pub fn window_rule(&self, app_id: Option<&str>, title: Option<&str>) -> WindowRule {
+        WindowRule::resolve(&self.rules, app_id, title)
     }
 
     pub fn setup_watcher(path: &Path, event_loop: LoopHandle<'static, CalloopData>) {
@@ -91,7 +173,7 @@ impl Config {
         let watcher = Watcher::new(path.to_owned(), tx);
         event_loop
             .insert_source(rx, move |event, _, data| match event {
-                ChannelEvent::Msg(()) => data.state.config.reload(),
+                ChannelEvent::Msg(()) => data.state.reload_config(),
                 ChannelEvent::Closed => (),
             })
             .unwrap();
@@ -106,7 +188,9 @@ impl FromStr for Config {
         let lua = Lua::new();
 
         let value = lua.load(s).eval()?;
-        Ok(lua.from_value(value)?)
+        let config: Config = lua.from_value(value)?;
+        config.validate()?;
+        Ok(config)
     }
 }
 
@@ -146,6 +230,162 @@ pub struct Outline {
     pub thickness: usize,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct Tiling {
+    #[serde(default)]
+    pub layout: LayoutKind,
+    #[serde(default = "default_master_ratio")]
+    pub master_ratio: f32,
+    #[serde(default = "default_master_count")]
+    pub master_count: usize,
+    #[serde(default = "default_column_width")]
+    pub column_width: f32,
+}
+
+impl Default for Tiling {
+    fn default() -> Self {
+        Self {
+            layout: LayoutKind::default(),
+            master_ratio: default_master_ratio(),
+            master_count: default_master_count(),
+            column_width: default_column_width(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LayoutKind {
+    #[default]
+    MasterStack,
+    Columns,
+    Dwindle,
+    Tree,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContainerLayout {
+    SplitH,
+    SplitV,
+    Tabbed,
+    Stacked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OutputConfig {
+    // Location of the top left corner in the global space, in logical pixels
+    pub position: Option<(i32, i32)>,
+    // Can be fractional, e.g. 1.5
+    pub scale: Option<f64>,
+    pub transform: Option<OutputTransform>,
+    pub mode: Option<OutputMode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct OutputMode {
+    pub width: i32,
+    pub height: i32,
+    // In Hz
+    #[serde(default = "default_refresh_rate")]
+    pub refresh: f64,
+}
+
+impl From<OutputMode> for Mode {
+    fn from(mode: OutputMode) -> Self {
+        let refresh = (mode.refresh * 1000.0).round() as i32;
+        Mode { size: (mode.width, mode.height).into(), refresh }
+    }
+}
+
+// Rotations are counter-clockwise, flipped ones are flipped around the vertical axis first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum OutputTransform {
+    Normal,
+    #[serde(rename = "90")]
+    Rotate90,
+    #[serde(rename = "180")]
+    Rotate180,
+    #[serde(rename = "270")]
+    Rotate270,
+    Flipped,
+    Flipped90,
+    Flipped180,
+    Flipped270,
+}
+
+impl From<OutputTransform> for Transform {
+    fn from(transform: OutputTransform) -> Self {
+        match transform {
+            OutputTransform::Normal => Transform::Normal,
+            OutputTransform::Rotate90 => Transform::_90,
+            OutputTransform::Rotate180 => Transform::_180,
+            OutputTransform::Rotate270 => Transform::_270,
+            OutputTransform::Flipped => Transform::Flipped,
+            OutputTransform::Flipped90 => Transform::Flipped90,
+            OutputTransform::Flipped180 => Transform::Flipped180,
+            OutputTransform::Flipped270 => Transform::Flipped270,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Grabs {
+    #[serde(deserialize_with = "deserialize_KeyModifiers", default = "default_grab_modifiers")]
+    pub modifiers: KeyModifiers,
+}
+
+impl Default for Grabs {
+    fn default() -> Self {
+        Self { modifiers: default_grab_modifiers() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Keyboard {
+    // Empty ones use the xkb defaults
+    #[serde(default)]
+    pub layout: String,
+    #[serde(default)]
+    pub variant: String,
+    pub options: Option<String>,
+    // In milliseconds
+    #[serde(default = "default_repeat_delay")]
+    pub repeat_delay: i32,
+    // In keys per second
+    #[serde(default = "default_repeat_rate")]
+    pub repeat_rate: i32,
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Self {
+            layout: String::new(),
+            variant: String::new(),
+            options: None,
+            repeat_delay: default_repeat_delay(),
+            repeat_rate: default_repeat_rate(),
+        }
+    }
+}
+
+impl Keyboard {
+    pub fn xkb_config(&self) -> XkbConfig<'_> {
+        XkbConfig {
+            layout: &self.layout,
+            variant: &self.variant,
+            options: self.options.clone(),
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug, Hash, Eq, PartialEq, Deserialize)]
 pub struct Pattern {
     #[serde(deserialize_with = "deserialize_KeyModifiers")]
@@ -170,6 +410,15 @@ pub struct KeyModifiers {
     pub logo: bool,
 }
 
+impl KeyModifiers {
+    pub fn contains(&self, other: &Self) -> bool {
+        (self.ctrl || !other.ctrl)
+            && (self.alt || !other.alt)
+            && (self.shift || !other.shift)
+            && (self.logo || !other.logo)
+    }
+}
+
 impl std::ops::AddAssign<KeyModifier> for KeyModifiers {
     fn add_assign(&mut self, rhs: KeyModifier) {
         match rhs {
@@ -256,16 +505,97 @@ pub enum Error {
     Io(#[from] IoError),
     #[error(transparent)]
     Lua(#[from] LuaError),
+    #[error("{0}")]
+    Invalid(String),
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Action {
     Exit,
     Close,
-    Spawn(String),
-    SwitchToWorkspace(usize),
-    MoveToWorkspace(usize),
+    Spawn(Spawn),
+    SwitchToWorkspace(WorkspaceId),
+    MoveToWorkspace(WorkspaceId),
+    NextWorkspace(WorkspaceStep),
+    PrevWorkspace(WorkspaceStep),
+    LastWorkspace,
+    MoveToNextWorkspace(WorkspaceStep),
+    MoveToPrevWorkspace(WorkspaceStep),
     ToggleFullscreen,
+    AdjustMasterRatio(f32),
+    AdjustMasterCount(isize),
+    SetLayout(LayoutKind),
+    FocusColumnLeft,
+    FocusColumnRight,
+    MoveColumnLeft,
+    MoveColumnRight,
+    ConsumeIntoColumn,
+    ExpelFromColumn,
+    ToggleSplitDirection,
+    Split(ContainerLayout),
+    SetContainerLayout(ContainerLayout),
+    FocusParent,
+    FocusChild,
+    MoveWindow(Direction),
+    ToggleFloating,
+    FocusDirection(Direction),
+    SwapDirection(Direction),
+    FocusNext,
+    FocusPrev,
+    SwitcherNext,
+    SwitcherPrev,
+    ToggleOverview,
+    MoveToScratchpad,
+    ToggleScratchpad,
+    FocusOutput(Direction),
+    MoveToOutput(Direction),
+    MoveWorkspaceToOutput(Direction),
+}
+
+// Workspaces are targeted by their 1-based number or by name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WorkspaceId {
+    Index(usize),
+    Name(String),
+}
+
+// How the next or previous workspace is found, by default the neighbouring one
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceStep {
+    // Continue from the other end after the last or before the first workspace
+    pub wrap: bool,
+    // Skip workspaces without windows
+    pub skip_empty: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Spawn {
+    Shell(String),
+    Argv(Vec<String>),
+    Exec(Exec),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Exec {
+    pub shell: Option<String>,
+    #[serde(default)]
+    pub argv: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub cwd: Option<PathBuf>,
+}
+
+impl From<Spawn> for Exec {
+    fn from(spawn: Spawn) -> Self {
+        match spawn {
+            Spawn::Shell(shell) => Exec { shell: Some(shell), ..Default::default() },
+            Spawn::Argv(argv) => Exec { argv, ..Default::default() },
+            Spawn::Exec(exec) => exec,
+        }
+    }
 }
 
 fn default_workspace_count() -> usize {
@@ -288,6 +618,34 @@ fn default_outline_thickness() -> usize {
     5
 }
 
+fn default_master_ratio() -> f32 {
+    0.5
+}
+
+fn default_master_count() -> usize {
+    1
+}
+
+fn default_column_width() -> f32 {
+    0.5
+}
+
+fn default_grab_modifiers() -> KeyModifiers {
+    KeyModifiers { logo: true, ..Default::default() }
+}
+
+fn default_refresh_rate() -> f64 {
+    60.0
+}
+
+fn default_repeat_delay() -> i32 {
+    180
+}
+
+fn default_repeat_rate() -> i32 {
+    60
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -296,4 +654,63 @@ mod tests {
     fn eval_default_config_ok() {
         assert!(Config::from_str(DEFAULT_CONFIG).is_ok());
     }
+
+    #[test]
+    fn reject_invalid_config() {
+        let config = Config::from_str("{ bindings = {}, outline = {}, workspace_count = 0 }");
+        assert!(matches!(config, Err(Error::Invalid(_))));
+        let config =
+            Config::from_str("{ bindings = {}, outline = {}, keyboard = { layout = 'nope' } }");
+        assert!(matches!(config, Err(Error::Invalid(_))));
+        for invalid in [
+            "tiling = { master_ratio = 1 }",
+            "tiling = { column_width = 0 }",
+            "outputs = { winit = { scale = 0 } }",
+            "outputs = { winit = { mode = { width = 0, height = 720 } } }",
+            "outputs = { winit = { mode = { width = 1280, height = 720, refresh = -60 } } }",
+            "rules = { { match = { app_id = 'foot' }, size = {640, 360} } }",
+        ] {
+            let config =
+                Config::from_str(&format!("{{ bindings = {{}}, outline = {{}}, {invalid} }}"));
+            assert!(matches!(config, Err(Error::Invalid(_))), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn deserialize_spawn() {
+        let lua = Lua::new();
+        let spawn = |s: &str| lua.from_value::<Spawn>(lua.load(s).eval().unwrap()).unwrap();
+
+        assert!(matches!(spawn(r#""konsole""#), Spawn::Shell(cmd) if cmd == "konsole"));
+        assert!(matches!(spawn(r#"{"foot", "-e", "htop"}"#), Spawn::Argv(argv) if argv.len() == 3));
+        let Spawn::Exec(exec) = spawn(r#"{argv = {"foot"}, env = {TERM = "xterm"}, cwd = "/tmp"}"#)
+        else {
+            panic!("Expected Spawn::Exec");
+        };
+        assert_eq!(exec.env.get("TERM").map(String::as_str), Some("xterm"));
+        assert_eq!(exec.cwd, Some(PathBuf::from("/tmp")));
+    }
+
+    #[test]
+    fn deserialize_outputs() {
+        let lua = Lua::new();
+        let outputs = lua
+            .from_value::<HashMap<String, OutputConfig>>(
+                lua.load(
+                    r#"{
+                        ["headless-0"] = { scale = 1.5, transform = "90", position = {0, 0} },
+                        ["headless-1"] = { mode = { width = 1280, height = 720, refresh = 59.94 } },
+                    }"#,
+                )
+                .eval()
+                .unwrap(),
+            )
+            .unwrap();
+
+        let output = &outputs["headless-0"];
+        assert_eq!(output.scale, Some(1.5));
+        assert_eq!(output.transform.map(Transform::from), Some(Transform::_90));
+        let mode = Mode::from(outputs["headless-1"].mode.unwrap());
+        assert_eq!(mode, Mode { size: (1280, 720).into(), refresh: 59_940 });
+    }
 }
@@ -0,0 +1,143 @@
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{Color, WorkspaceId};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    #[serde(rename = "match")]
+    pub matcher: Matcher,
+    pub workspace: Option<WorkspaceId>,
+    pub floating: Option<bool>,
+    pub size: Option<(i32, i32)>,
+    pub position: Option<(i32, i32)>,
+    pub fullscreen: Option<bool>,
+    pub opacity: Option<f32>,
+    pub outline_color: Option<Color>,
+}
+
+// Every given property has to match, a matcher without any matches all windows
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Matcher {
+    pub app_id: Option<TextMatch>,
+    pub title: Option<TextMatch>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum TextMatch {
+    Exact(String),
+    Regex {
+        #[serde(deserialize_with = "deserialize_Regex")]
+        regex: Regex,
+    },
+}
+
+// What all rules matching a window add up to, later rules win
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowRule {
+    pub workspace: Option<WorkspaceId>,
+    pub floating: Option<bool>,
+    pub size: Option<(i32, i32)>,
+    pub position: Option<(i32, i32)>,
+    pub fullscreen: Option<bool>,
+    pub opacity: Option<f32>,
+    pub outline_color: Option<Color>,
+}
+
+impl Rule {
+    pub fn matches(&self, app_id: Option<&str>, title: Option<&str>) -> bool {
+        let is_match = |matcher: &Option<TextMatch>, text: Option<&str>| match matcher {
+            Some(matcher) => text.is_some_and(|text| matcher.matches(text)),
+            None => true,
+        };
+        is_match(&self.matcher.app_id, app_id) && is_match(&self.matcher.title, title)
+    }
+}
+
+impl TextMatch {
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Self::Exact(s) => s == text,
+            Self::Regex { regex } => regex.is_match(text),
+        }
+    }
+}
+
+impl WindowRule {
+    pub fn resolve<'a>(
+        rules: impl IntoIterator<Item = &'a Rule>,
+        app_id: Option<&str>,
+        title: Option<&str>,
+    ) -> Self {
+        rules.into_iter().filter(|rule| rule.matches(app_id, title)).fold(
+            Self::default(),
+            |resolved, rule| Self {
+                workspace: rule.workspace.clone().or(resolved.workspace),
+                floating: rule.floating.or(resolved.floating),
+                size: rule.size.or(resolved.size),
+                position: rule.position.or(resolved.position),
+                fullscreen: rule.fullscreen.or(resolved.fullscreen),
+                opacity: rule.opacity.or(resolved.opacity),
+                outline_color: rule.outline_color.or(resolved.outline_color),
+            },
+        )
+    }
+}
+
+#[allow(non_snake_case)]
+fn deserialize_Regex<'de, D>(deserializer: D) -> Result<Regex, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Regex::new(&s).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use mlua::{Lua, LuaSerdeExt};
+
+    use super::*;
+
+    #[test]
+    fn resolve_rules() {
+        let lua = Lua::new();
+        let rules = lua
+            .from_value::<Vec<Rule>>(
+                lua.load(
+                    r#"{
+                        { match = { app_id = "foot" }, workspace = 2, opacity = 0.9 },
+                        { match = { title = { regex = "^notes" } }, floating = true },
+                        { match = { app_id = "foot", title = { regex = "notes$" } }, workspace = "notes" },
+                    }"#,
+                )
+                .eval()
+                .unwrap(),
+            )
+            .unwrap();
+
+        let rule = WindowRule::resolve(&rules, Some("foot"), Some("my notes"));
+        assert_eq!(rule.workspace, Some(WorkspaceId::Name("notes".into())));
+        assert_eq!(rule.opacity, Some(0.9));
+        assert_eq!(rule.floating, None);
+        let rule = WindowRule::resolve(&rules, Some("foot"), None);
+        assert_eq!(rule.workspace, Some(WorkspaceId::Index(2)));
+
+        let rule = WindowRule::resolve(&rules, None, Some("notes"));
+        assert_eq!(rule, WindowRule { floating: Some(true), ..Default::default() });
+
+        assert_eq!(WindowRule::resolve(&rules, Some("Foot"), None), WindowRule::default());
+    }
+
+    #[test]
+    fn reject_unknown_fields() {
+        let lua = Lua::new();
+        let rule = |s: &str| lua.from_value::<Rule>(lua.load(s).eval().unwrap());
+        assert!(rule(r#"{ match = { app_id = "foot" }, opacity = 0.9 }"#).is_ok());
+        assert!(rule(r#"{ match = { app_id = "foot" }, opactiy = 0.9 }"#).is_err());
+        assert!(rule(r#"{ match = { class = "foot" } }"#).is_err());
+    }
+}
@@ -2,7 +2,7 @@ use std::os::fd::OwnedFd;
 
 use smithay::backend::renderer::utils::on_commit_buffer_handler;
 use smithay::desktop::{PopupKind, Window};
-use smithay::input::pointer::CursorImageStatus;
+use smithay::input::pointer::{CursorImageStatus, GrabStartData as PointerGrabStartData};
 use smithay::input::{Seat, SeatHandler, SeatState};
 use smithay::output::Output;
 use smithay::reexports::wayland_protocols;
@@ -10,8 +10,8 @@ use smithay::reexports::wayland_server::protocol::wl_buffer::WlBuffer;
 use smithay::reexports::wayland_server::protocol::wl_output::WlOutput;
 use smithay::reexports::wayland_server::protocol::wl_seat::WlSeat;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::reexports::wayland_server::Client;
-use smithay::utils::Serial;
+use smithay::reexports::wayland_server::{Client, Resource};
+use smithay::utils::{IsAlive, Serial};
 use smithay::wayland::buffer::BufferHandler;
 use smithay::wayland::compositor::{
     get_parent, is_sync_subsurface, with_states, CompositorClientState, CompositorHandler,
@@ -33,7 +33,10 @@ use smithay::{
     delegate_xdg_decoration, delegate_xdg_shell,
 };
 use wayland_protocols::xdg::decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode;
+use wayland_protocols::xdg::shell::server::xdg_toplevel::ResizeEdge;
 
+use crate::ipc::message::Event;
+use crate::ipc::query;
 use crate::state::{ClientState, State};
 
 impl BufferHandler for State {
@@ -51,8 +54,9 @@ impl XdgShellHandler for State {
 
     fn new_toplevel(&mut self, surface: ToplevelSurface) {
         let window = Window::new_wayland_window(surface);
-        self.shell.workspaces.current_mut().map_window(window.clone(), (0, 0), true);
-        self.set_focus(Some(window));
+        self.shell.workspaces.add_window(window.clone());
+        self.set_focus(Some(window.clone()));
+        self.emit_window(&window, Event::WindowOpened);
     }
 
     fn new_popup(&mut self, surface: PopupSurface, positioner: PositionerState) {
@@ -70,8 +74,36 @@ impl XdgShellHandler for State {
         // TODO Handle popup grab here
     }
 
-    fn toplevel_destroyed(&mut self, _surface: ToplevelSurface) {
-        self.shell.workspaces.current_mut().refresh();
+    fn move_request(&mut self, surface: ToplevelSurface, seat: WlSeat, serial: Serial) {
+        let Some((window, start_data)) = self.check_grab(&surface, &seat, serial) else {
+            return;
+        };
+        self.start_move(&window, start_data, serial);
+    }
+
+    fn resize_request(
+        &mut self,
+        surface: ToplevelSurface,
+        seat: WlSeat,
+        serial: Serial,
+        edges: ResizeEdge,
+    ) {
+        let Some((window, start_data)) = self.check_grab(&surface, &seat, serial) else {
+            return;
+        };
+        self.start_resize(&window, start_data, edges.into(), serial);
+    }
+
+    fn toplevel_destroyed(&mut self, surface: ToplevelSurface) {
+        self.shell.workspaces.remove_toplevel(surface.wl_surface());
+        if self.get_focus().is_some_and(|focus| !focus.alive()) {
+            let window = self.shell.workspaces.current().windows().next().cloned();
+            self.set_focus(window);
+        }
+        self.emit(|_| {
+            let (app_id, title) = query::app_id_and_title_of(surface.wl_surface());
+            Some(Event::WindowClosed { app_id, title })
+        });
     }
 
     fn reposition_request(
@@ -84,6 +116,34 @@ impl XdgShellHandler for State {
     }
 }
 
+impl State {
+    // Client initiated grabs are only valid during an implicit grab of the same client
+    fn check_grab(
+        &self,
+        surface: &ToplevelSurface,
+        seat: &WlSeat,
+        serial: Serial,
+    ) -> Option<(Window, PointerGrabStartData<State>)> {
+        let pointer = Seat::<State>::from_resource(seat)?.get_pointer()?;
+        if !pointer.has_grab(serial) {
+            return None;
+        }
+        let start_data = pointer.grab_start_data()?;
+        let (focus, _) = start_data.focus.as_ref()?;
+        if !focus.id().same_client_as(&surface.wl_surface().id()) {
+            return None;
+        }
+        let window = self
+            .shell
+            .workspaces
+            .current()
+            .windows()
+            .find(|w| w.toplevel().is_some_and(|toplevel| toplevel == surface))
+            .cloned()?;
+        Some((window, start_data))
+    }
+}
+
 impl XdgDecorationHandler for State {
     fn new_decoration(&mut self, toplevel: ToplevelSurface) {
         toplevel.with_pending_state(|state| state.decoration_mode = Some(Mode::ServerSide));
@@ -124,8 +184,7 @@ impl CompositorHandler for State {
             if let Some(window) = self
                 .shell
                 .workspaces
-                .current()
-                .windows()
+                .visible_windows()
                 .find(|w| w.toplevel().expect("Wayland window").wl_surface() == &root)
             {
                 window.on_commit();
@@ -134,8 +193,7 @@ impl CompositorHandler for State {
         if let Some(window) = self
             .shell
             .workspaces
-            .current()
-            .windows()
+            .visible_windows()
             .find(|w| w.toplevel().expect("Wayland window").wl_surface() == surface)
             .cloned()
         {
@@ -150,8 +208,10 @@ impl CompositorHandler for State {
             });
 
             if !initial_configure_sent {
+                self.apply_rules(&window);
                 window.toplevel().expect("Wayland window").send_configure();
             }
+            self.commit_resize(&window);
         }
     }
 }
@@ -1,53 +1,38 @@
 use anyhow::Result;
 use smithay::backend::input::{
-    AbsolutePositionEvent, Axis, AxisSource, ButtonState, Event, InputBackend, InputEvent,
+    AbsolutePositionEvent, Axis, AxisSource, ButtonState, Event as _, InputBackend, InputEvent,
     KeyState, KeyboardKeyEvent, PointerAxisEvent, PointerButtonEvent,
 };
 use smithay::input::keyboard::FilterResult;
 use smithay::input::pointer::{AxisFrame, ButtonEvent, MotionEvent};
+use smithay::output::Output;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::utils::SERIAL_COUNTER;
+use smithay::utils::{Logical, Point, SERIAL_COUNTER};
 use tracing::{debug, error};
 
-use crate::config::Action;
+use crate::config::{Action, KeyModifiers, WorkspaceStep};
+use crate::ipc::message::Event;
+use crate::shell::output_area;
 use crate::state::State;
 
 impl State {
-    pub fn handle_input<I: InputBackend>(&mut self, event: InputEvent<I>) {
+    // Absolute pointer positions are relative to `output`
+    pub fn handle_input<I: InputBackend>(&mut self, event: InputEvent<I>, output: &Output) {
         match event {
             InputEvent::Keyboard { event } => {
                 let action = self.action_from_event::<I>(event);
                 if let Err(err) = self.process_action(action) {
                     error!(?err);
                 }
+                self.update_switcher();
+                self.update_focus_cycle();
             }
             InputEvent::PointerMotionAbsolute { event, .. } => {
-                let output_geo = self.shell.workspaces.output_geometry().unwrap();
-
-                let point = event.position_transformed(output_geo.size);
-
-                let serial = SERIAL_COUNTER.next_serial();
-
-                let pointer = self.seat.get_pointer().unwrap();
-
-                let under = self.shell.workspaces.current().surface_under(point);
-
-                if let Some((window, _loc)) = self
-                    .shell
-                    .workspaces
-                    .current()
-                    .window_under(pointer.current_location())
-                    .map(|(w, l)| (w.clone(), l))
-                {
-                    self.set_focus(Some(window));
-                }
-
-                pointer.motion(
-                    self,
-                    under,
-                    &MotionEvent { location: point, serial, time: event.time_msec() },
-                );
-                pointer.frame(self);
+                let Some(area) = output_area(output) else {
+                    return;
+                };
+                let point = event.position_transformed(area.size) + area.loc.to_f64();
+                self.pointer_motion(point, event.time_msec());
             }
             InputEvent::PointerButton { event, .. } => {
                 let pointer = self.seat.get_pointer().unwrap();
@@ -59,21 +44,31 @@ impl State {
 
                 let button_state = event.state();
 
+                if self.shell.overview.is_some() {
+                    self.overview_button(pointer.current_location(), button_state);
+                    return;
+                }
+
                 if ButtonState::Pressed == button_state && !pointer.is_grabbed() {
                     if let Some((window, _loc)) = self
                         .shell
                         .workspaces
-                        .current()
                         .window_under(pointer.current_location())
                         .map(|(w, l)| (w.clone(), l))
                     {
-                        self.shell.workspaces.current_mut().raise_window(&window, true);
-                        self.set_focus(Some(window));
-                        self.shell.workspaces.current().windows().for_each(|window| {
+                        self.shell.workspaces.raise_window(&window, true);
+                        self.set_focus(Some(window.clone()));
+                        self.shell.workspaces.visible_windows().for_each(|window| {
                             window.toplevel().expect("Wayland window").send_pending_configure();
                         });
+                        let modifiers = KeyModifiers::from(keyboard.modifier_state());
+                        if self.config.grabs.modifiers == modifiers
+                            && self.start_pointer_grab(&window, button, serial)
+                        {
+                            return;
+                        }
                     } else {
-                        self.shell.workspaces.current().windows().for_each(|window| {
+                        self.shell.workspaces.visible_windows().for_each(|window| {
                             window.set_activated(false);
                             window.toplevel().expect("Wayland window").send_pending_configure();
                         });
@@ -129,11 +124,48 @@ impl State {
         }
     }
 
+    // Moves the pointer to a point in the global space
+    fn pointer_motion(&mut self, point: Point<f64, Logical>, time: u32) {
+        let pointer = self.seat.get_pointer().unwrap();
+
+        // Crossing onto another output activates it, moving within one leaves it as is
+        let workspaces = &self.shell.workspaces;
+        let from = workspaces.output_under(pointer.current_location());
+        let to = workspaces.output_under(point).filter(|&to| from != Some(to));
+        if let Some(index) = to.and_then(|to| workspaces.workspace_on(to)) {
+            if let Err(err) = self.switch_workspace(index) {
+                error!(?err);
+            }
+        }
+
+        let serial = SERIAL_COUNTER.next_serial();
+
+        // Clients get no pointer events while the overview is shown
+        let is_overview = self.shell.overview.is_some();
+        if is_overview {
+            self.overview_motion(point);
+        }
+        let under = self.shell.workspaces.surface_under(point).filter(|_| !is_overview);
+
+        if let Some((window, _loc)) = self
+            .shell
+            .workspaces
+            .window_under(point)
+            .filter(|_| !is_overview)
+            .map(|(w, l)| (w.clone(), l))
+        {
+            self.set_focus(Some(window));
+        }
+
+        pointer.motion(self, under, &MotionEvent { location: point, serial, time });
+        pointer.frame(self);
+    }
+
     fn action_from_event<I: InputBackend>(&mut self, event: I::KeyboardKeyEvent) -> Option<Action> {
         let code = event.key_code();
         let state = event.state();
         let serial = SERIAL_COUNTER.next_serial();
-        let time = Event::time_msec(&event);
+        let time = event.time_msec();
         let keyboard = self.seat.get_keyboard().unwrap();
 
         keyboard.input(self, code, state, serial, time, |data, modifiers, handle| {
@@ -150,32 +182,218 @@ impl State {
         })
     }
 
-    fn process_action(&mut self, action: Option<Action>) -> Result<()> {
+    pub fn process_action(&mut self, action: Option<Action>) -> Result<()> {
         match action {
-            Some(Action::Exit) => self.is_running = false,
+            Some(Action::Exit) => {
+                self.shell.processes.terminate_all();
+                self.is_running = false;
+            }
             Some(Action::Close) => {
                 let window = self.get_focus();
                 self.shell.close(window);
                 let window = self.shell.workspaces.current().windows().next().cloned();
                 self.set_focus(window);
             }
-            Some(Action::Spawn(cmd)) => self.shell.spawn(cmd),
-            Some(Action::SwitchToWorkspace(n)) => {
-                self.shell.switch_to(n);
+            Some(Action::Spawn(spawn)) => {
+                self.shell.spawn(spawn)?;
+            }
+            Some(Action::SwitchToWorkspace(id)) => {
+                if self.shell.switch_to(&id)? {
+                    self.emit_workspace_switched();
+                }
                 let window = self.shell.workspaces.current().windows().next().cloned();
                 self.set_focus(window);
             }
-            Some(Action::MoveToWorkspace(n)) => {
+            Some(Action::MoveToWorkspace(id)) => {
                 let window = self.get_focus();
-                self.shell.move_to(window, n);
-                self.set_focus(None);
+                self.shell.move_to(window, &id)?;
+                // Focus stays on the current workspace
+                let window = self.shell.workspaces.current().windows().next().cloned();
+                self.set_focus(window);
+            }
+            Some(Action::NextWorkspace(step)) => {
+                self.switch_relative(1, step)?;
+            }
+            Some(Action::PrevWorkspace(step)) => {
+                self.switch_relative(-1, step)?;
+            }
+            Some(Action::LastWorkspace) => {
+                if let Some(previous) = self.shell.workspaces.previous_index() {
+                    self.switch_workspace(previous)?;
+                }
+            }
+            Some(Action::MoveToNextWorkspace(step)) => {
+                self.move_relative(1, step)?;
+            }
+            Some(Action::MoveToPrevWorkspace(step)) => {
+                self.move_relative(-1, step)?;
             }
             Some(Action::ToggleFullscreen) => {
                 let window = self.get_focus();
                 self.shell.toggle_fullscreen(window.as_ref());
+                if let Some(window) = window.as_ref() {
+                    self.emit_window(window, Event::FullscreenToggled);
+                }
+            }
+            Some(Action::AdjustMasterRatio(delta)) => {
+                self.shell.workspaces.adjust_master_ratio(delta);
+            }
+            Some(Action::AdjustMasterCount(delta)) => {
+                self.shell.workspaces.adjust_master_count(delta);
+            }
+            Some(Action::SetLayout(kind)) => {
+                self.shell.workspaces.set_layout(kind);
+            }
+            Some(Action::FocusColumnLeft) => {
+                let window = self.shell.workspaces.focus_column(-1);
+                self.set_focus(window);
+            }
+            Some(Action::FocusColumnRight) => {
+                let window = self.shell.workspaces.focus_column(1);
+                self.set_focus(window);
+            }
+            Some(Action::MoveColumnLeft) => {
+                self.shell.workspaces.move_column(-1);
+            }
+            Some(Action::MoveColumnRight) => {
+                self.shell.workspaces.move_column(1);
+            }
+            Some(Action::ConsumeIntoColumn) => {
+                self.shell.workspaces.consume_into_column();
+            }
+            Some(Action::ExpelFromColumn) => {
+                self.shell.workspaces.expel_from_column();
+            }
+            Some(Action::ToggleSplitDirection) => {
+                self.shell.workspaces.toggle_split_direction();
+            }
+            Some(Action::Split(layout)) => {
+                self.shell.workspaces.split(layout);
+            }
+            Some(Action::SetContainerLayout(layout)) => {
+                self.shell.workspaces.set_container_layout(layout);
+            }
+            Some(Action::FocusParent) => {
+                self.shell.workspaces.focus_parent();
+            }
+            Some(Action::FocusChild) => {
+                self.shell.workspaces.focus_child();
+            }
+            Some(Action::MoveWindow(direction)) => {
+                self.shell.workspaces.move_window(direction);
+            }
+            Some(Action::ToggleFloating) => {
+                if let Some(window) = self.get_focus() {
+                    self.shell.workspaces.toggle_floating(&window);
+                }
+            }
+            Some(Action::FocusDirection(direction)) => {
+                let window = self
+                    .get_focus()
+                    .and_then(|focus| self.shell.workspaces.window_in_direction(&focus, direction));
+                if let Some(window) = window {
+                    self.shell.workspaces.raise_floating(&window);
+                    self.set_focus(Some(window));
+                }
+            }
+            Some(Action::SwapDirection(direction)) => {
+                if let Some(focus) = self.get_focus() {
+                    let window = self.shell.workspaces.window_in_direction(&focus, direction);
+                    if let Some(window) = window {
+                        self.shell.workspaces.swap_windows(&focus, &window);
+                    }
+                }
+            }
+            Some(Action::FocusNext) => {
+                self.cycle_focus(1);
+            }
+            Some(Action::FocusPrev) => {
+                self.cycle_focus(-1);
+            }
+            Some(Action::SwitcherNext) => {
+                self.step_switcher(1);
+            }
+            Some(Action::SwitcherPrev) => {
+                self.step_switcher(-1);
+            }
+            Some(Action::ToggleOverview) => {
+                self.toggle_overview();
+            }
+            Some(Action::MoveToScratchpad) => {
+                if let Some(window) = self.get_focus() {
+                    self.shell.workspaces.move_to_scratchpad(&window);
+                    if !self.shell.workspaces.visible_windows().any(|w| *w == window) {
+                        let window = self.shell.workspaces.current().windows().next().cloned();
+                        self.set_focus(window);
+                    }
+                }
+            }
+            Some(Action::FocusOutput(direction)) => {
+                let workspaces = &self.shell.workspaces;
+                let output = workspaces.output_in_direction(direction);
+                if let Some(index) = output.and_then(|output| workspaces.workspace_on(&output)) {
+                    self.switch_workspace(index)?;
+                }
+            }
+            Some(Action::MoveToOutput(direction)) => {
+                let workspaces = &self.shell.workspaces;
+                let output = workspaces.output_in_direction(direction);
+                let index = output.and_then(|output| workspaces.workspace_on(&output));
+                if let Some((window, index)) = self.get_focus().zip(index) {
+                    self.shell.workspaces.move_to(window.clone(), index)?;
+                    self.set_focus(Some(window));
+                }
+            }
+            Some(Action::MoveWorkspaceToOutput(direction)) => {
+                if let Some(output) = self.shell.workspaces.output_in_direction(direction) {
+                    self.shell.workspaces.move_workspace_to_output(&output);
+                }
+            }
+            Some(Action::ToggleScratchpad) => {
+                let window = self
+                    .shell
+                    .workspaces
+                    .toggle_scratchpad()
+                    .or_else(|| self.shell.workspaces.current().windows().next().cloned());
+                self.set_focus(window);
             }
             _ => (),
         }
         Ok(())
     }
+
+    fn switch_relative(&mut self, delta: isize, step: WorkspaceStep) -> Result<()> {
+        match self.shell.workspaces.relative(delta, step) {
+            Some(index) => self.switch_workspace(index),
+            None => Ok(()),
+        }
+    }
+
+    fn switch_workspace(&mut self, index: usize) -> Result<()> {
+        if self.shell.workspaces.switch_to(index)? {
+            self.emit_workspace_switched();
+        }
+        let window = self.shell.workspaces.current().windows().next().cloned();
+        self.set_focus(window);
+        Ok(())
+    }
+
+    // Moves the focused window and follows it
+    fn move_relative(&mut self, delta: isize, step: WorkspaceStep) -> Result<()> {
+        let index = self.shell.workspaces.relative(delta, step);
+        let Some((window, index)) = self.get_focus().zip(index) else {
+            return Ok(());
+        };
+        let workspaces = &mut self.shell.workspaces;
+        workspaces.move_to(window.clone(), index)?;
+        // Moving may remove other workspaces and shift the index
+        let Some(index) = workspaces.workspace_of(&window) else {
+            return Ok(());
+        };
+        if workspaces.switch_to(index)? {
+            self.emit_workspace_switched();
+        }
+        self.set_focus(Some(window));
+        Ok(())
+    }
 }
@@ -0,0 +1,55 @@
+use std::env;
+use std::io::{BufRead, BufReader, Lines};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+
+use super::message::{Event, Reply, Request, Response};
+use super::{socket_path, write_message, SOCKET_ENV};
+
+pub struct Client {
+    stream: UnixStream,
+    lines: Lines<BufReader<UnixStream>>,
+}
+
+impl Client {
+    pub fn connect(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path.to_owned(),
+            None => default_path()?,
+        };
+        let stream =
+            UnixStream::connect(&path).with_context(|| format!("Failed to connect to {path:?}"))?;
+        let lines = BufReader::new(stream.try_clone()?).lines();
+        Ok(Self { stream, lines })
+    }
+
+    pub fn request(&mut self, request: &Request) -> Result<Response> {
+        write_message(&self.stream, request)?;
+        let reply = self.read::<Reply>()?.context("Connection closed")?;
+        reply.map_err(anyhow::Error::msg)
+    }
+
+    pub fn subscribe(mut self) -> Result<impl Iterator<Item = Result<Event>>> {
+        self.request(&Request::Subscribe)?;
+        Ok(std::iter::from_fn(move || self.read::<Event>().transpose()))
+    }
+
+    fn read<T: DeserializeOwned>(&mut self) -> Result<Option<T>> {
+        match self.lines.next() {
+            Some(line) => Ok(Some(serde_json::from_str(&line?)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn default_path() -> Result<PathBuf> {
+    if let Some(path) = env::var_os(SOCKET_ENV) {
+        return Ok(path.into());
+    }
+    let wayland_socket = env::var_os("WAYLAND_DISPLAY")
+        .with_context(|| format!("Neither {SOCKET_ENV} nor WAYLAND_DISPLAY is set"))?;
+    socket_path(&wayland_socket)
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Action, LayoutKind};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Request {
+    Action(Action),
+    Workspaces,
+    Windows,
+    Outputs,
+    Subscribe,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Response {
+    Handled,
+    Subscribed,
+    Workspaces(Vec<WorkspaceInfo>),
+    Windows(Vec<WindowInfo>),
+    Outputs(Vec<OutputInfo>),
+}
+
+pub type Reply = Result<Response, String>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Event {
+    WorkspaceSwitched { from: Option<usize>, to: usize },
+    FocusChanged(WindowInfo),
+    WindowOpened(WindowInfo),
+    WindowClosed { app_id: Option<String>, title: Option<String> },
+    FullscreenToggled(WindowInfo),
+    ConfigReloaded,
+    ConfigFailed { error: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceInfo {
+    pub index: usize,
+    pub name: Option<String>,
+    pub is_current: bool,
+    pub window_count: usize,
+    pub layout: LayoutKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowInfo {
+    pub workspace: usize,
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+    pub geometry: Geometry,
+    pub is_focused: bool,
+    pub is_fullscreen: bool,
+    pub is_floating: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Geometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputInfo {
+    pub name: String,
+    pub workspace: Option<usize>,
+    pub is_focused: bool,
+    pub mode: Option<ModeInfo>,
+    pub transform: String,
+    pub scale: f64,
+    pub location: (i32, i32),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ModeInfo {
+    pub width: i32,
+    pub height: i32,
+    pub refresh: i32,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WorkspaceId;
+
+    #[test]
+    fn request_format() {
+        let request: Request =
+            serde_json::from_str(r#"{"Action":{"SwitchToWorkspace":2}}"#).unwrap();
+        assert!(matches!(
+            request,
+            Request::Action(Action::SwitchToWorkspace(WorkspaceId::Index(2)))
+        ));
+        let request: Request =
+            serde_json::from_str(r#"{"Action":{"MoveToWorkspace":"web"}}"#).unwrap();
+        assert!(matches!(
+            request,
+            Request::Action(Action::MoveToWorkspace(WorkspaceId::Name(name))) if name == "web"
+        ));
+
+        let request: Request = serde_json::from_str(r#"{"Action":{"Spawn":["foot"]}}"#).unwrap();
+        assert!(matches!(request, Request::Action(Action::Spawn(_))));
+
+        let reply: Reply = Ok(Response::Handled);
+        assert_eq!(serde_json::to_string(&reply).unwrap(), r#"{"Ok":"Handled"}"#);
+    }
+}
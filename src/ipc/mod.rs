@@ -0,0 +1,176 @@
+use std::ffi::OsStr;
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use smithay::reexports::calloop::generic::Generic;
+use smithay::reexports::calloop::{Interest, LoopHandle, Mode, PostAction};
+use tracing::{debug, info, warn};
+
+use self::message::{Event, Reply, Request, Response};
+use crate::state::{CalloopData, State};
+use crate::PKG_NAME;
+
+pub mod client;
+pub mod message;
+pub mod query;
+
+pub const SOCKET_ENV: &str = "WELL_SOCKET";
+
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+// Clients sending more than this without a newline are dropped
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+pub struct IpcServer {
+    path: PathBuf,
+}
+
+impl IpcServer {
+    pub fn new(
+        wayland_socket: &OsStr,
+        event_loop: LoopHandle<'static, CalloopData>,
+    ) -> Result<Self> {
+        let path = socket_path(wayland_socket)?;
+
+        // A stale socket may be left behind after a crash
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Failed to bind IPC socket {path:?}"))?;
+        listener.set_nonblocking(true)?;
+
+        event_loop
+            .insert_source(
+                Generic::new(listener, Interest::READ, Mode::Level),
+                |_, listener, data| {
+                    loop {
+                        match listener.as_ref().accept() {
+                            Ok((stream, _)) => {
+                                if let Err(err) = accept(stream, &data.state.event_loop) {
+                                    warn!(?err, "Failed to accept IPC client");
+                                }
+                            }
+                            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                            // Like running out of file descriptors, which should not stop the
+                            // compositor
+                            Err(err) => {
+                                warn!(?err, "Failed to accept IPC connection");
+                                break;
+                            }
+                        }
+                    }
+                    Ok(PostAction::Continue)
+                },
+            )
+            .map_err(|_| anyhow!("Failed to initialize IPC source"))?;
+
+        std::env::set_var(SOCKET_ENV, &path);
+        info!(?path, "IPC listening");
+
+        Ok(Self { path })
+    }
+}
+
+pub fn socket_path(wayland_socket: &OsStr) -> Result<PathBuf> {
+    let runtime_dir = xdg::BaseDirectories::new()?
+        .get_runtime_directory()
+        .context("XDG_RUNTIME_DIR is not set")?
+        .to_owned();
+    Ok(runtime_dir.join(format!("{PKG_NAME}-ipc.{}.sock", wayland_socket.to_string_lossy())))
+}
+
+#[derive(Default)]
+pub struct Subscribers(Vec<UnixStream>);
+
+impl Subscribers {
+    pub fn add(&mut self, stream: UnixStream) {
+        self.0.push(stream);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn emit(&mut self, event: &Event) {
+        debug!(?event, "IPC event");
+        self.0.retain(|stream| match write_message(stream, event) {
+            Ok(()) => true,
+            Err(err) => {
+                debug!(?err, "Dropping IPC subscriber");
+                false
+            }
+        });
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn accept(stream: UnixStream, event_loop: &LoopHandle<'static, CalloopData>) -> Result<()> {
+    debug!("New IPC client");
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut buffer = Vec::new();
+    event_loop
+        .insert_source(Generic::new(stream, Interest::READ, Mode::Level), move |_, stream, data| {
+            let mut stream: &UnixStream = stream.as_ref();
+            let mut chunk = [0; 4096];
+            match stream.read(&mut chunk) {
+                Ok(0) => return Ok(PostAction::Remove),
+                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {
+                    return Ok(PostAction::Continue)
+                }
+                Err(err) => {
+                    warn!(?err, "Failed to read IPC request");
+                    return Ok(PostAction::Remove);
+                }
+            }
+
+            while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let line = buffer.drain(..=end).collect::<Vec<_>>();
+                let reply = handle_request(&mut data.state, stream, &line);
+                if let Err(err) = write_message(stream, &reply) {
+                    warn!(?err, "Failed to write IPC reply");
+                    return Ok(PostAction::Remove);
+                }
+            }
+            if buffer.len() > MAX_REQUEST_SIZE {
+                warn!(size = buffer.len(), "Dropping IPC client with an oversized request");
+                return Ok(PostAction::Remove);
+            }
+            Ok(PostAction::Continue)
+        })
+        .map_err(|_| anyhow!("Failed to initialize IPC client source"))?;
+    Ok(())
+}
+
+fn handle_request(state: &mut State, stream: &UnixStream, line: &[u8]) -> Reply {
+    let request =
+        serde_json::from_slice::<Request>(line).map_err(|err| format!("Invalid request: {err}"))?;
+    debug!(?request, "IPC request");
+    match request {
+        Request::Action(action) => state
+            .process_action(Some(action))
+            .map(|()| Response::Handled)
+            .map_err(|err| format!("{err:#}")),
+        Request::Workspaces => Ok(Response::Workspaces(query::workspaces(state))),
+        Request::Windows => Ok(Response::Windows(query::windows(state))),
+        Request::Outputs => Ok(Response::Outputs(query::outputs(state))),
+        Request::Subscribe => {
+            let stream = stream.try_clone().map_err(|err| err.to_string())?;
+            state.subscribers.add(stream);
+            Ok(Response::Subscribed)
+        }
+    }
+}
+
+pub fn write_message(mut stream: impl Write, message: &impl serde::Serialize) -> Result<()> {
+    let mut message = serde_json::to_vec(message)?;
+    message.push(b'\n');
+    stream.write_all(&message)?;
+    Ok(())
+}
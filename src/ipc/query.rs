@@ -0,0 +1,104 @@
+use smithay::desktop::Window;
+use smithay::output::Output;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::utils::{Logical, Rectangle};
+use smithay::wayland::compositor::with_states;
+use smithay::wayland::shell::xdg::XdgToplevelSurfaceData;
+
+use super::message::{Geometry, ModeInfo, OutputInfo, WindowInfo, WorkspaceInfo};
+use crate::shell::Workspaces;
+use crate::state::State;
+
+pub fn workspaces(state: &State) -> Vec<WorkspaceInfo> {
+    let workspaces = &state.shell.workspaces;
+    workspaces
+        .iter()
+        .map(|(index, workspace)| WorkspaceInfo {
+            index: index + 1,
+            name: workspace.name.clone(),
+            is_current: index == workspaces.current_index(),
+            window_count: workspace.windows().count(),
+            layout: workspace.layout.kind(),
+        })
+        .collect()
+}
+
+pub fn windows(state: &State) -> Vec<WindowInfo> {
+    let focus = state.get_focus();
+    state
+        .shell
+        .workspaces
+        .iter()
+        .flat_map(|(index, workspace)| workspace.windows().map(move |window| (index, window)))
+        .map(|(index, window)| window_info(state, index, window, focus.as_ref()))
+        .collect()
+}
+
+pub fn window(state: &State, window: &Window) -> Option<WindowInfo> {
+    let (index, _) =
+        state.shell.workspaces.iter().find(|(_, w)| w.windows().any(|w| w == window))?;
+    Some(window_info(state, index, window, state.get_focus().as_ref()))
+}
+
+fn window_info(state: &State, index: usize, window: &Window, focus: Option<&Window>) -> WindowInfo {
+    let (_, workspace) = state.shell.workspaces.iter().nth(index).unwrap();
+    let geometry = workspace.space.element_geometry(window).unwrap_or_default();
+    let (app_id, title) = app_id_and_title(window);
+    WindowInfo {
+        workspace: index + 1,
+        app_id,
+        title,
+        geometry: geometry.into(),
+        is_focused: focus == Some(window),
+        is_fullscreen: state.shell.workspaces.is_fullscreen(window),
+        is_floating: state.shell.workspaces.is_floating(window),
+    }
+}
+
+pub fn outputs(state: &State) -> Vec<OutputInfo> {
+    let workspaces = &state.shell.workspaces;
+    workspaces.outputs().map(|output| output_info(workspaces, output)).collect()
+}
+
+fn output_info(workspaces: &Workspaces, output: &Output) -> OutputInfo {
+    let location = output.current_location();
+    OutputInfo {
+        name: output.name(),
+        workspace: workspaces.workspace_on(output).map(|index| index + 1),
+        is_focused: workspaces.output.as_ref() == Some(output),
+        mode: output.current_mode().map(|mode| ModeInfo {
+            width: mode.size.w,
+            height: mode.size.h,
+            refresh: mode.refresh,
+        }),
+        transform: format!("{:?}", output.current_transform()),
+        scale: output.current_scale().fractional_scale(),
+        location: (location.x, location.y),
+    }
+}
+
+pub fn app_id_and_title(window: &Window) -> (Option<String>, Option<String>) {
+    let Some(toplevel) = window.toplevel() else {
+        return (None, None);
+    };
+    app_id_and_title_of(toplevel.wl_surface())
+}
+
+pub fn app_id_and_title_of(surface: &WlSurface) -> (Option<String>, Option<String>) {
+    with_states(surface, |states| {
+        states
+            .data_map
+            .get::<XdgToplevelSurfaceData>()
+            .map(|data| {
+                let data = data.lock().unwrap();
+                (data.app_id.clone(), data.title.clone())
+            })
+            .unwrap_or_default()
+    })
+}
+
+impl From<Rectangle<i32, Logical>> for Geometry {
+    fn from(rect: Rectangle<i32, Logical>) -> Self {
+        Self { x: rect.loc.x, y: rect.loc.y, width: rect.size.w, height: rect.size.h }
+    }
+}
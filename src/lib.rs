@@ -0,0 +1,13 @@
+pub mod backend;
+pub mod cli;
+pub mod config;
+pub mod handlers;
+pub mod input;
+pub mod ipc;
+pub mod logger;
+pub mod render;
+pub mod shell;
+pub mod state;
+
+pub const PKG_NAME: &str = env!("CARGO_PKG_NAME");
+pub const PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
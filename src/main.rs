@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
@@ -6,37 +7,53 @@ use smithay::reexports::calloop::{EventLoop, Interest, Mode, PostAction};
 use smithay::reexports::wayland_server::Display;
 use smithay::wayland::socket::ListeningSocketSource;
 use tracing::{error, info, warn};
+use well::backend::BackendState;
+use well::cli::{Cli, Command};
+use well::config::Config;
+use well::ipc::IpcServer;
+use well::logger;
+use well::state::{CalloopData, ClientState, State};
 
-use crate::backend::BackendState;
-use crate::state::{CalloopData, ClientState, State};
-
-mod backend;
-mod config;
-mod handlers;
-mod input;
-mod logger;
-mod render;
-mod shell;
-mod state;
+fn main() -> Result<()> {
+    let cli = Cli::parse()?;
 
-const PKG_NAME: &str = env!("CARGO_PKG_NAME");
-const PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
+    match cli.command {
+        Some(Command::Help) => {
+            println!("{}", Cli::usage());
+            return Ok(());
+        }
+        Some(Command::Version) => {
+            println!("{}", Cli::version());
+            return Ok(());
+        }
+        None => (),
+    }
 
-fn main() -> Result<()> {
     logger::init();
 
+    if cli.check_config {
+        check_config(cli.config.clone().or_else(Config::find));
+    }
+
+    let config = Config::new(cli.config.as_deref())?;
+
     let display = Display::new()?;
 
     let mut event_loop = EventLoop::try_new().context("Failed to initialize event loop")?;
     let signal = event_loop.get_signal();
     let handle = event_loop.handle();
 
-    let state = State::new(&display.handle(), handle.clone());
-    let backend = BackendState::new(handle.clone());
+    let state = State::new(&display.handle(), handle.clone(), config);
+    let backend =
+        BackendState::new(cli.backend, handle.clone()).context("Failed to initialize backend")?;
     let mut data = CalloopData { backend, state };
     BackendState::init(&mut data);
 
-    let source = ListeningSocketSource::new_auto()?;
+    let source = match cli.socket {
+        Some(name) => ListeningSocketSource::with_name(&name)
+            .with_context(|| format!("Failed to bind wayland socket {name:?}"))?,
+        None => ListeningSocketSource::new_auto()?,
+    };
     let socket_name = source.socket_name().to_os_string();
 
     handle
@@ -67,6 +84,15 @@ fn main() -> Result<()> {
     std::env::set_var("WAYLAND_DISPLAY", socket_name.as_os_str());
     info!("Listening on {socket_name:?}");
 
+    let _ipc = IpcServer::new(&socket_name, handle.clone())
+        .map_err(|err| error!(?err, "Failed to start IPC server"))
+        .ok();
+
+    data.state.autostart(false);
+    if let Some(command) = cli.session {
+        data.state.spawn_session(command)?;
+    }
+
     let timeout = None;
     event_loop.run(timeout, &mut data, move |data| {
         if !data.state.is_running {
@@ -80,3 +106,20 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+fn check_config(path: Option<PathBuf>) -> ! {
+    let Some(path) = path else {
+        eprintln!("No configuration file found");
+        std::process::exit(1);
+    };
+    match Config::try_from(path.as_path()) {
+        Ok(_) => {
+            println!("Configuration file {path:?} is valid");
+            std::process::exit(0);
+        }
+        Err(err) => {
+            eprintln!("Configuration file {path:?} is invalid: {err}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,58 +1,85 @@
-use anyhow::Result;
+use smithay::backend::renderer::element::solid::SolidColorRenderElement;
 use smithay::backend::renderer::element::surface::WaylandSurfaceRenderElement;
 use smithay::backend::renderer::element::{Element, Id, RenderElement};
-use smithay::backend::renderer::gles::{
-    GlesError, GlesFrame, GlesRenderer, GlesTexProgram, GlesTexture, Uniform,
-};
 use smithay::backend::renderer::utils::CommitCounter;
+use smithay::backend::renderer::Renderer;
 use smithay::render_elements;
-use smithay::utils::{Buffer, Logical, Physical, Rectangle, Scale, Transform};
+use smithay::utils::{Buffer, Logical, Physical, Rectangle, Scale, Size, Transform};
 
+use super::OutlineRenderer;
 use crate::config::Color;
 
 render_elements! {
-    pub OutputRenderElement<=GlesRenderer>;
-    Window = WaylandSurfaceRenderElement<GlesRenderer>,
-    RoundedWindow = RoundedElement,
+    pub OutputRenderElement<R> where R: OutlineRenderer;
+    Window = WaylandSurfaceRenderElement<R>,
+    RoundedWindow = RoundedElement<R>,
+    Solid = SolidColorRenderElement,
 }
 
-pub struct RoundedElement {
-    color: Color,
+pub struct RoundedElement<R: OutlineRenderer> {
+    alpha: f32,
+    // Scale the texture was rendered at
+    pub(super) buffer_scale: f64,
+    pub(super) color: Color,
     commit_counter: CommitCounter,
     geometry: Rectangle<i32, Logical>,
     id: Id,
-    program: GlesTexProgram,
-    radius: f32,
-    texture: GlesTexture,
+    pub(super) program: R::OutlineProgram,
+    pub(super) radius: f32,
+    src_size: Size<i32, Logical>,
+    pub(super) texture: <R as Renderer>::TextureId,
     transform: Transform,
-    thickness: f32,
+    pub(super) thickness: f32,
 }
 
-impl RoundedElement {
+impl<R: OutlineRenderer> RoundedElement<R> {
     pub fn new(
         color: Color,
+        alpha: f32,
         geometry: Rectangle<i32, Logical>,
-        program: GlesTexProgram,
+        program: R::OutlineProgram,
         radius: f32,
-        texture: GlesTexture,
+        texture: <R as Renderer>::TextureId,
         transform: Transform,
         thickness: f32,
     ) -> Self {
         Self {
+            alpha,
+            buffer_scale: 1.0,
             color,
             commit_counter: CommitCounter::default(),
             geometry,
             id: Id::new(),
             program,
             radius,
+            src_size: geometry.size,
             texture,
             transform,
             thickness,
         }
     }
+
+    // Draws what would cover the original geometry into another one, like for thumbnails
+    pub fn scaled_to(self, geometry: Rectangle<i32, Logical>) -> Self {
+        Self { geometry, ..self }
+    }
+
+    pub fn with_buffer_scale(self, buffer_scale: f64) -> Self {
+        Self { buffer_scale, ..self }
+    }
+
+    // Physical pixels per logical one where drawn, to size the outline with. `src` covers the
+    // outline as well, while the texture holds the window only
+    pub(super) fn dst_scale(
+        &self,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+    ) -> f64 {
+        dst.size.w as f64 / src.size.to_logical(self.buffer_scale, self.transform).w
+    }
 }
 
-impl Element for RoundedElement {
+impl<R: OutlineRenderer> Element for RoundedElement<R> {
     fn id(&self) -> &Id {
         &self.id
     }
@@ -62,9 +89,12 @@ impl Element for RoundedElement {
     }
 
     fn src(&self) -> Rectangle<f64, Buffer> {
-        let scale = 1.0;
-        let mut src =
-            self.geometry.to_f64().to_buffer(scale, self.transform(), &self.geometry.size.to_f64());
+        let size = self.src_size.to_f64();
+        let mut src = Rectangle::from_loc_and_size(self.geometry.loc.to_f64(), size).to_buffer(
+            self.buffer_scale,
+            self.transform(),
+            &size,
+        );
         src.loc.x = 0.0;
         src.loc.y = 0.0;
 
@@ -78,34 +108,20 @@ impl Element for RoundedElement {
     fn transform(&self) -> Transform {
         self.transform
     }
+
+    fn alpha(&self) -> f32 {
+        self.alpha
+    }
 }
 
-impl RenderElement<GlesRenderer> for RoundedElement {
+impl<R: OutlineRenderer> RenderElement<R> for RoundedElement<R> {
     fn draw(
         &self,
-        frame: &mut GlesFrame<'_>,
+        frame: &mut <R as Renderer>::Frame<'_>,
         src: Rectangle<f64, Buffer>,
         dst: Rectangle<i32, Physical>,
         damage: &[Rectangle<i32, Physical>],
-    ) -> Result<(), GlesError> {
-        let program = Some(&self.program);
-
-        let additional_uniforms = vec![
-            Uniform::new("color", self.color),
-            Uniform::new("thickness", self.thickness),
-            Uniform::new("radius", self.radius),
-            Uniform::new("size", (dst.size.w as f32, dst.size.h as f32)),
-        ];
-
-        frame.render_texture_from_to(
-            &self.texture,
-            src,
-            dst,
-            damage,
-            self.transform(),
-            self.alpha(),
-            program,
-            &additional_uniforms,
-        )
+    ) -> Result<(), <R as Renderer>::Error> {
+        R::draw_rounded(frame, self, src, dst, damage)
     }
 }
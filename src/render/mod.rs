@@ -1,4 +1,25 @@
+use smithay::backend::renderer::{ImportAll, ImportMem, Renderer};
+use smithay::utils::{Buffer, Physical, Rectangle};
+
+use self::element::RoundedElement;
+
 pub mod element;
+pub mod overlay;
+pub mod pixman;
 pub mod shader;
 
 pub const CLEAR_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+
+pub trait OutlineRenderer: Renderer + ImportAll + ImportMem + Sized {
+    type OutlineProgram: Clone;
+
+    fn outline_program(&self) -> Self::OutlineProgram;
+
+    fn draw_rounded(
+        frame: &mut Self::Frame<'_>,
+        element: &RoundedElement<Self>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), Self::Error>;
+}
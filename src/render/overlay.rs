@@ -0,0 +1,86 @@
+use smithay::backend::renderer::element::solid::{SolidColorBuffer, SolidColorRenderElement};
+use smithay::backend::renderer::element::Kind;
+use smithay::utils::{Logical, Rectangle, Size};
+
+use super::element::OutputRenderElement;
+use super::OutlineRenderer;
+
+pub const PADDING: i32 = 16;
+
+// Lays out `n` cells in rows centered in `area`, returns the panel around them and the cells
+pub fn grid(
+    area: Rectangle<i32, Logical>,
+    n: usize,
+    cell: Size<i32, Logical>,
+) -> (Rectangle<i32, Logical>, Vec<Rectangle<i32, Logical>>) {
+    let n = n as i32;
+    let columns = ((area.size.w - PADDING) / (cell.w + PADDING)).clamp(1, n.max(1));
+    let rows = (n + columns - 1) / columns;
+    let size =
+        Size::from((columns * (cell.w + PADDING) + PADDING, rows * (cell.h + PADDING) + PADDING));
+    let loc = area.loc + ((area.size.w - size.w) / 2, (area.size.h - size.h) / 2).into();
+    let cells = (0..n)
+        .map(|i| {
+            let x = PADDING + (i % columns) * (cell.w + PADDING);
+            let y = PADDING + (i / columns) * (cell.h + PADDING);
+            Rectangle::from_loc_and_size(loc + (x, y).into(), cell)
+        })
+        .collect();
+    (Rectangle::from_loc_and_size(loc, size), cells)
+}
+
+// Scales `size` down to fit into `area` keeping its aspect ratio, centered
+pub fn fit(size: Size<i32, Logical>, area: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+    let scale = (area.size.w as f64 / size.w.max(1) as f64)
+        .min(area.size.h as f64 / size.h.max(1) as f64)
+        .min(1.0);
+    let fitted = Size::from(((size.w as f64 * scale) as i32, (size.h as f64 * scale) as i32));
+    let offset = ((area.size.w - fitted.w) / 2, (area.size.h - fitted.h) / 2);
+    Rectangle::from_loc_and_size(area.loc + offset.into(), fitted)
+}
+
+pub fn solid<R: OutlineRenderer>(
+    geometry: Rectangle<i32, Logical>,
+    color: [f32; 4],
+    scale: f64,
+) -> OutputRenderElement<R> {
+    let buffer = SolidColorBuffer::new(geometry.size, color);
+    let location = geometry.loc.to_physical_precise_round(scale);
+    OutputRenderElement::Solid(SolidColorRenderElement::from_buffer(
+        &buffer,
+        location,
+        scale,
+        1.0,
+        Kind::Unspecified,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_wraps_into_rows() {
+        let area = Rectangle::from_loc_and_size((0, 0), (1000, 800));
+        let cell = Size::from((200, 150));
+        let (panel, cells) = grid(area, 6, cell);
+        // 4 columns of 216 fit into 1000 - 16
+        assert_eq!(panel.size, Size::from((4 * 216 + 16, 2 * 166 + 16)));
+        assert_eq!(cells[4].loc, panel.loc + (PADDING, 166 + PADDING).into());
+        let bottom = cells.iter().map(|cell| cell.loc.y + cell.size.h).max().unwrap();
+        assert_eq!(bottom + PADDING, panel.loc.y + panel.size.h);
+
+        let (panel, cells) = grid(area, 1, cell);
+        assert_eq!(panel.loc, (384, 309).into());
+        assert_eq!(cells.len(), 1);
+    }
+
+    #[test]
+    fn fit_keeps_aspect_ratio() {
+        let area = Rectangle::from_loc_and_size((10, 10), (200, 150));
+        let fitted = fit(Size::from((1000, 500)), area);
+        assert_eq!(fitted, Rectangle::from_loc_and_size((10, 35), (200, 100)));
+        let fitted = fit(Size::from((100, 50)), area);
+        assert_eq!(fitted, Rectangle::from_loc_and_size((60, 60), (100, 50)));
+    }
+}
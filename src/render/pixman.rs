@@ -0,0 +1,156 @@
+use smithay::backend::renderer::element::Element;
+use smithay::backend::renderer::pixman::{PixmanError, PixmanFrame, PixmanRenderer};
+use smithay::backend::renderer::Frame;
+use smithay::utils::{Buffer, Physical, Rectangle, Size};
+
+use super::element::RoundedElement;
+use super::OutlineRenderer;
+
+impl OutlineRenderer for PixmanRenderer {
+    type OutlineProgram = ();
+
+    fn outline_program(&self) -> Self::OutlineProgram {}
+
+    fn draw_rounded(
+        frame: &mut PixmanFrame<'_>,
+        element: &RoundedElement<Self>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), PixmanError> {
+        let scale = element.dst_scale(src, dst);
+        let thickness = (element.thickness as f64 * scale).round() as i32;
+        let radius = (element.radius as f64 * scale).round() as i32;
+
+        // Solid colors are drawn premultiplied
+        let alpha = element.alpha();
+        let [r, g, b] = element.color.map(|c| c * alpha);
+        for span in outline_spans(dst.size, radius, thickness) {
+            if let Some(damage) = span_damage(damage, span) {
+                frame.draw_solid(span_dst(dst, span), &damage, [r, g, b, alpha])?;
+            }
+        }
+
+        let inner_size = dst.size - Size::from((thickness * 2, thickness * 2));
+        let buffer_thickness = element.thickness as f64 * element.buffer_scale;
+        let texture_size = src.size - Size::from((buffer_thickness * 2., buffer_thickness * 2.));
+        let texture_scale =
+            texture_size.to_logical(1.0, element.transform()).w / inner_size.w as f64;
+        for span in rounded_spans(inner_size, radius - thickness) {
+            let src = span.to_f64().to_logical(1.0).to_buffer(
+                texture_scale,
+                element.transform(),
+                &inner_size.to_f64().to_logical(1.0),
+            );
+            let span =
+                Rectangle::from_loc_and_size(span.loc + (thickness, thickness).into(), span.size);
+            if let Some(damage) = span_damage(damage, span) {
+                frame.render_texture_from_to(
+                    &element.texture,
+                    src,
+                    span_dst(dst, span),
+                    &damage,
+                    element.transform(),
+                    element.alpha(),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn span_dst(
+    dst: Rectangle<i32, Physical>,
+    span: Rectangle<i32, Physical>,
+) -> Rectangle<i32, Physical> {
+    Rectangle::from_loc_and_size(dst.loc + span.loc, span.size)
+}
+
+fn span_damage(
+    damage: &[Rectangle<i32, Physical>],
+    span: Rectangle<i32, Physical>,
+) -> Option<Vec<Rectangle<i32, Physical>>> {
+    let damage = damage
+        .iter()
+        .filter_map(|rect| rect.intersection(span))
+        .map(|rect| Rectangle::from_loc_and_size(rect.loc - span.loc, rect.size))
+        .collect::<Vec<_>>();
+    (!damage.is_empty()).then_some(damage)
+}
+
+fn rounded_spans(size: Size<i32, Physical>, radius: i32) -> Vec<Rectangle<i32, Physical>> {
+    merge_rows((0..size.h).map(|y| {
+        let (x0, x1) = row_extent(size, radius, y);
+        (y, x0, x1)
+    }))
+}
+
+fn outline_spans(
+    size: Size<i32, Physical>,
+    radius: i32,
+    thickness: i32,
+) -> Vec<Rectangle<i32, Physical>> {
+    let inner_size = size - Size::from((thickness * 2, thickness * 2));
+    let inner_radius = radius - thickness;
+    merge_rows((0..size.h).flat_map(|y| {
+        let (x0, x1) = row_extent(size, radius, y);
+        if y < thickness || y >= size.h - thickness {
+            return vec![(y, x0, x1)];
+        }
+        let (i0, i1) = row_extent(inner_size, inner_radius, y - thickness);
+        vec![(y, x0, i0 + thickness), (y, i1 + thickness, x1)]
+    }))
+}
+
+fn row_extent(size: Size<i32, Physical>, radius: i32, y: i32) -> (i32, i32) {
+    let radius = radius.min(size.w / 2).min(size.h / 2).max(0);
+    let row = if y < radius {
+        y
+    } else if y >= size.h - radius {
+        size.h - 1 - y
+    } else {
+        return (0, size.w);
+    };
+    let dy = radius as f64 - row as f64 - 0.5;
+    let dx = (radius as f64 * radius as f64 - dy * dy).max(0.).sqrt();
+    let inset = (radius as f64 - dx).round() as i32;
+    (inset, size.w - inset)
+}
+
+fn merge_rows(rows: impl IntoIterator<Item = (i32, i32, i32)>) -> Vec<Rectangle<i32, Physical>> {
+    let mut spans: Vec<Rectangle<i32, Physical>> = Vec::new();
+    for (y, x0, x1) in rows.into_iter().filter(|(_, x0, x1)| x1 > x0) {
+        match spans.iter_mut().rev().take(2).find(|span| {
+            span.loc.y + span.size.h == y && span.loc.x == x0 && span.size.w == x1 - x0
+        }) {
+            Some(span) => span.size.h += 1,
+            None => spans.push(Rectangle::from_loc_and_size((x0, y), (x1 - x0, 1))),
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(spans: &[Rectangle<i32, Physical>]) -> i32 {
+        spans.iter().map(|span| span.size.w * span.size.h).sum()
+    }
+
+    #[test]
+    fn square_outline_covers_ring() {
+        let size = Size::from((100, 50));
+        assert_eq!(rounded_spans(size, 0), vec![Rectangle::from_loc_and_size((0, 0), size)]);
+        assert_eq!(area(&outline_spans(size, 0, 5)), 100 * 50 - 90 * 40);
+    }
+
+    #[test]
+    fn rounded_outline_does_not_overlap_window() {
+        let size = Size::from((100, 50));
+        let inner = rounded_spans(size - Size::from((10, 10)), 19);
+        let outline = outline_spans(size, 24, 5);
+        assert_eq!(area(&outline) + area(&inner), area(&rounded_spans(size, 24)));
+    }
+}
@@ -1,11 +1,18 @@
-use smithay::backend::renderer::gles::{GlesRenderer, GlesTexProgram, UniformName, UniformType};
+use smithay::backend::renderer::element::Element;
+use smithay::backend::renderer::gles::{
+    GlesError, GlesFrame, GlesRenderer, GlesTexProgram, Uniform, UniformName, UniformType,
+};
+use smithay::utils::{Buffer, Physical, Rectangle};
+
+use super::element::RoundedElement;
+use super::OutlineRenderer;
 
 static OUTLINE_SHADER: &str = include_str!("./shader.frag");
 
 pub struct OutlineShader;
 
 impl OutlineShader {
-    pub fn compile(renderer: &mut GlesRenderer) {
+    pub fn compile(renderer: &mut GlesRenderer) -> Result<(), GlesError> {
         let src = OUTLINE_SHADER;
         let additional_uniforms = &[
             UniformName::new("color", UniformType::_3f),
@@ -13,11 +20,50 @@ impl OutlineShader {
             UniformName::new("radius", UniformType::_1f),
             UniformName::new("size", UniformType::_2f),
         ];
-        let program = renderer.compile_custom_texture_shader(src, additional_uniforms).unwrap();
+        let program = renderer.compile_custom_texture_shader(src, additional_uniforms)?;
         renderer.egl_context().user_data().insert_if_missing(|| program);
+        Ok(())
     }
 
     pub fn program(renderer: &GlesRenderer) -> GlesTexProgram {
         renderer.egl_context().user_data().get().cloned().unwrap()
     }
 }
+
+impl OutlineRenderer for GlesRenderer {
+    type OutlineProgram = GlesTexProgram;
+
+    fn outline_program(&self) -> Self::OutlineProgram {
+        OutlineShader::program(self)
+    }
+
+    fn draw_rounded(
+        frame: &mut GlesFrame<'_>,
+        element: &RoundedElement<Self>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), GlesError> {
+        let program = Some(&element.program);
+        // `size` is in physical pixels, the outline has to be too
+        let scale = element.dst_scale(src, dst) as f32;
+
+        let additional_uniforms = vec![
+            Uniform::new("color", element.color),
+            Uniform::new("thickness", element.thickness * scale),
+            Uniform::new("radius", element.radius * scale),
+            Uniform::new("size", (dst.size.w as f32, dst.size.h as f32)),
+        ];
+
+        frame.render_texture_from_to(
+            &element.texture,
+            src,
+            dst,
+            damage,
+            element.transform(),
+            element.alpha(),
+            program,
+            &additional_uniforms,
+        )
+    }
+}
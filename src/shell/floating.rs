@@ -0,0 +1,19 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use smithay::desktop::Window;
+
+pub struct IsFloating(AtomicBool);
+
+impl IsFloating {
+    pub fn get(window: &Window) -> bool {
+        window.user_data().get::<Self>().map(|d| d.0.load(Ordering::Relaxed)).unwrap_or_default()
+    }
+
+    pub fn set(window: &Window, is: bool) {
+        if !window.user_data().insert_if_missing(|| Self(is.into())) {
+            if let Some(d) = window.user_data().get::<Self>() {
+                d.0.store(is, Ordering::Relaxed);
+            }
+        }
+    }
+}
@@ -3,8 +3,10 @@ use std::cell::RefCell;
 use smithay::desktop::Window;
 use smithay::input::Seat;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::utils::SERIAL_COUNTER;
+use smithay::utils::{IsAlive, SERIAL_COUNTER};
 
+use crate::config::KeyModifiers;
+use crate::ipc::message::Event;
 use crate::state::State;
 
 struct CurrentFocus(RefCell<Option<Window>>);
@@ -21,23 +23,129 @@ impl CurrentFocus {
     }
 }
 
+// Windows in most recently used order
+struct FocusHistory(RefCell<Vec<Window>>);
+
+impl FocusHistory {
+    fn get(seat: &Seat<State>) -> Vec<Window> {
+        seat.user_data()
+            .get::<Self>()
+            .map(|d| d.0.borrow().iter().filter(|w| w.alive()).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn push(seat: &Seat<State>, window: &Window) {
+        seat.user_data().insert_if_missing(|| Self(RefCell::new(Vec::new())));
+        let mut windows = seat.user_data().get::<Self>().unwrap().0.borrow_mut();
+        windows.retain(|w| w != window && w.alive());
+        windows.insert(0, window.clone());
+    }
+}
+
+// Modifiers held while cycling focus, the window it ends on is recorded once they are released
+struct FocusCycle(RefCell<Option<KeyModifiers>>);
+
+impl FocusCycle {
+    fn get(seat: &Seat<State>) -> Option<KeyModifiers> {
+        seat.user_data().get::<Self>().and_then(|d| d.0.borrow().clone())
+    }
+
+    fn set(seat: &Seat<State>, modifiers: Option<KeyModifiers>) {
+        if !seat.user_data().insert_if_missing(|| Self(RefCell::new(modifiers.clone()))) {
+            seat.user_data().get::<Self>().unwrap().0.replace(modifiers);
+        }
+    }
+}
+
 impl State {
     pub fn get_focus(&self) -> Option<Window> {
         CurrentFocus::get(&self.seat)
     }
 
     pub fn set_focus(&mut self, window: Option<Window>) -> Option<()> {
-        let surface = window.as_ref()?.toplevel()?.wl_surface();
-        set_keyboard_focus(self, surface.clone());
-        CurrentFocus::set(&self.seat, window);
+        self.update_focus(window, true)
+    }
+
+    pub fn focus_history(&self) -> Vec<Window> {
+        FocusHistory::get(&self.seat)
+    }
+
+    // Steps through the windows of the current workspace in most recently used order,
+    // which is left as is so that repeated steps reach every window
+    pub fn cycle_focus(&mut self, delta: isize) -> Option<()> {
+        let workspace = self.shell.workspaces.current();
+        let mut windows = self.focus_history();
+        windows.retain(|window| workspace.windows().any(|w| w == window));
+        let unused =
+            workspace.windows().filter(|w| !windows.contains(w)).cloned().collect::<Vec<_>>();
+        windows.extend(unused);
+
+        let index = self.get_focus().and_then(|focus| windows.iter().position(|w| *w == focus));
+        let index = index.map_or(0, |index| index as isize + delta);
+        let window = windows.get(index.rem_euclid(windows.len().max(1) as isize) as usize)?.clone();
+        self.shell.workspaces.raise_floating(&window);
+        self.update_focus(Some(window.clone()), false)?;
+
+        match self.held_modifiers() {
+            Some(modifiers) => FocusCycle::set(&self.seat, Some(modifiers)),
+            None => FocusHistory::push(&self.seat, &window),
+        }
+        Some(())
+    }
+
+    // The modifiers whose release commits a focus cycle or the switcher. Without any held,
+    // like when sent over IPC, there is nothing to release and the step commits at once
+    pub fn held_modifiers(&self) -> Option<KeyModifiers> {
+        let keyboard = self.seat.get_keyboard()?;
+        let modifiers =
+            KeyModifiers { shift: false, ..KeyModifiers::from(keyboard.modifier_state()) };
+        (modifiers != KeyModifiers::default()).then_some(modifiers)
+    }
+
+    pub fn update_focus_cycle(&mut self) {
+        let Some(modifiers) = FocusCycle::get(&self.seat) else {
+            return;
+        };
+        let Some(keyboard) = self.seat.get_keyboard() else {
+            return;
+        };
+        if !KeyModifiers::from(keyboard.modifier_state()).contains(&modifiers) {
+            FocusCycle::set(&self.seat, None);
+            if let Some(window) = self.get_focus() {
+                FocusHistory::push(&self.seat, &window);
+            }
+        }
+    }
+
+    fn update_focus(&mut self, window: Option<Window>, is_recorded: bool) -> Option<()> {
+        // No window, like on an empty workspace, leaves the keyboard without focus
+        let Some(surface) = window.as_ref().and_then(|w| w.toplevel()).map(|t| t.wl_surface())
+        else {
+            set_keyboard_focus(self, None);
+            CurrentFocus::set(&self.seat, None);
+            return Some(());
+        };
+        set_keyboard_focus(self, Some(surface.clone()));
+        let is_changed = self.get_focus() != window;
+        CurrentFocus::set(&self.seat, window.clone());
+        if is_recorded {
+            FocusHistory::push(&self.seat, window.as_ref()?);
+        }
+        // Focusing a window on another output switches to its workspace
+        if self.shell.workspaces.focus(window.as_ref()?) {
+            self.emit_workspace_switched();
+        }
+        if is_changed {
+            self.emit_window(window.as_ref()?, Event::FocusChanged);
+        }
         Some(())
     }
 }
 
-fn set_keyboard_focus(state: &mut State, surface: WlSurface) {
+fn set_keyboard_focus(state: &mut State, surface: Option<WlSurface>) {
     let serial = SERIAL_COUNTER.next_serial();
 
     if let Some(handle) = state.seat.get_keyboard() {
-        handle.set_focus(state, Some(surface), serial);
+        handle.set_focus(state, surface, serial);
     }
 }
@@ -0,0 +1,169 @@
+// Grabs pass gestures through to the client unchanged
+macro_rules! forward_gestures {
+    () => {
+        fn gesture_swipe_begin(
+            &mut self,
+            data: &mut $crate::state::State,
+            handle: &mut smithay::input::pointer::PointerInnerHandle<'_, $crate::state::State>,
+            event: &smithay::input::pointer::GestureSwipeBeginEvent,
+        ) {
+            handle.gesture_swipe_begin(data, event);
+        }
+
+        fn gesture_swipe_update(
+            &mut self,
+            data: &mut $crate::state::State,
+            handle: &mut smithay::input::pointer::PointerInnerHandle<'_, $crate::state::State>,
+            event: &smithay::input::pointer::GestureSwipeUpdateEvent,
+        ) {
+            handle.gesture_swipe_update(data, event);
+        }
+
+        fn gesture_swipe_end(
+            &mut self,
+            data: &mut $crate::state::State,
+            handle: &mut smithay::input::pointer::PointerInnerHandle<'_, $crate::state::State>,
+            event: &smithay::input::pointer::GestureSwipeEndEvent,
+        ) {
+            handle.gesture_swipe_end(data, event);
+        }
+
+        fn gesture_pinch_begin(
+            &mut self,
+            data: &mut $crate::state::State,
+            handle: &mut smithay::input::pointer::PointerInnerHandle<'_, $crate::state::State>,
+            event: &smithay::input::pointer::GesturePinchBeginEvent,
+        ) {
+            handle.gesture_pinch_begin(data, event);
+        }
+
+        fn gesture_pinch_update(
+            &mut self,
+            data: &mut $crate::state::State,
+            handle: &mut smithay::input::pointer::PointerInnerHandle<'_, $crate::state::State>,
+            event: &smithay::input::pointer::GesturePinchUpdateEvent,
+        ) {
+            handle.gesture_pinch_update(data, event);
+        }
+
+        fn gesture_pinch_end(
+            &mut self,
+            data: &mut $crate::state::State,
+            handle: &mut smithay::input::pointer::PointerInnerHandle<'_, $crate::state::State>,
+            event: &smithay::input::pointer::GesturePinchEndEvent,
+        ) {
+            handle.gesture_pinch_end(data, event);
+        }
+
+        fn gesture_hold_begin(
+            &mut self,
+            data: &mut $crate::state::State,
+            handle: &mut smithay::input::pointer::PointerInnerHandle<'_, $crate::state::State>,
+            event: &smithay::input::pointer::GestureHoldBeginEvent,
+        ) {
+            handle.gesture_hold_begin(data, event);
+        }
+
+        fn gesture_hold_end(
+            &mut self,
+            data: &mut $crate::state::State,
+            handle: &mut smithay::input::pointer::PointerInnerHandle<'_, $crate::state::State>,
+            event: &smithay::input::pointer::GestureHoldEndEvent,
+        ) {
+            handle.gesture_hold_end(data, event);
+        }
+    };
+}
+
+use smithay::desktop::Window;
+use smithay::input::pointer::{Focus, GrabStartData as PointerGrabStartData};
+use smithay::utils::{Point, Rectangle, Serial};
+
+pub use self::move_grab::MoveSurfaceGrab;
+pub use self::resize_grab::{Edges, ResizeSurfaceGrab};
+use super::floating::IsFloating;
+use super::fullscreen::IsFullscreen;
+use crate::state::State;
+
+mod move_grab;
+mod resize_grab;
+
+// Button codes from linux/input-event-codes.h
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+
+impl State {
+    // Modifier+left drag moves and modifier+right drag resizes a floating window
+    pub fn start_pointer_grab(&mut self, window: &Window, button: u32, serial: Serial) -> bool {
+        let pointer = self.seat.get_pointer().unwrap();
+        let location = pointer.current_location();
+        let start_data = PointerGrabStartData { focus: None, button, location };
+        match button {
+            BTN_LEFT => self.start_move(window, start_data, serial),
+            BTN_RIGHT => {
+                let Some((_, window_location)) = self.shell.workspaces.window_under(location)
+                else {
+                    return false;
+                };
+                // Resize from the edges closest to the pointer
+                let size = window.geometry().size.to_f64();
+                let center = window_location.to_f64() + Point::from((size.w / 2., size.h / 2.));
+                let edges = Edges {
+                    left: location.x < center.x,
+                    right: location.x >= center.x,
+                    top: location.y < center.y,
+                    bottom: location.y >= center.y,
+                };
+                self.start_resize(window, start_data, edges, serial)
+            }
+            _ => false,
+        }
+    }
+
+    // Only floating windows can be moved and resized
+    pub fn start_move(
+        &mut self,
+        window: &Window,
+        start_data: PointerGrabStartData<State>,
+        serial: Serial,
+    ) -> bool {
+        if !IsFloating::get(window) || IsFullscreen::get(window) {
+            return false;
+        }
+        let Some(location) = self.shell.workspaces.window_location(window) else {
+            return false;
+        };
+        let grab = MoveSurfaceGrab {
+            start_data,
+            window: window.clone(),
+            initial_window_location: location,
+        };
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+        true
+    }
+
+    pub fn start_resize(
+        &mut self,
+        window: &Window,
+        start_data: PointerGrabStartData<State>,
+        edges: Edges,
+        serial: Serial,
+    ) -> bool {
+        if !IsFloating::get(window) || IsFullscreen::get(window) {
+            return false;
+        }
+        let Some(location) = self.shell.workspaces.window_location(window) else {
+            return false;
+        };
+        let grab = ResizeSurfaceGrab {
+            start_data,
+            window: window.clone(),
+            edges,
+            initial_window_geometry: Rectangle::from_loc_and_size(location, window.geometry().size),
+        };
+        let pointer = self.seat.get_pointer().unwrap();
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+        true
+    }
+}
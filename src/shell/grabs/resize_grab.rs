@@ -0,0 +1,198 @@
+use std::cell::RefCell;
+
+use smithay::desktop::Window;
+use smithay::input::pointer::{
+    AxisFrame, ButtonEvent, GrabStartData as PointerGrabStartData, MotionEvent, PointerGrab,
+    PointerInnerHandle, RelativeMotionEvent,
+};
+use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel::{
+    ResizeEdge, State as ToplevelState,
+};
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::utils::{Logical, Point, Rectangle, Size};
+use smithay::wayland::compositor::with_states;
+use smithay::wayland::shell::xdg::SurfaceCachedState;
+
+use crate::state::State;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Edges {
+    pub left: bool,
+    pub right: bool,
+    pub top: bool,
+    pub bottom: bool,
+}
+
+impl From<ResizeEdge> for Edges {
+    fn from(edge: ResizeEdge) -> Self {
+        let (left, right, top, bottom) = match edge {
+            ResizeEdge::Top => (false, false, true, false),
+            ResizeEdge::Bottom => (false, false, false, true),
+            ResizeEdge::Left => (true, false, false, false),
+            ResizeEdge::Right => (false, true, false, false),
+            ResizeEdge::TopLeft => (true, false, true, false),
+            ResizeEdge::TopRight => (false, true, true, false),
+            ResizeEdge::BottomLeft => (true, false, false, true),
+            ResizeEdge::BottomRight => (false, true, false, true),
+            _ => (false, false, false, false),
+        };
+        Self { left, right, top, bottom }
+    }
+}
+
+// Set on a window while it is resized, as dragging its left or top edge moves it once the
+// client has committed the new size
+#[derive(Default)]
+pub struct ResizeState(RefCell<Option<Resize>>);
+
+#[derive(Debug, Clone, Copy)]
+struct Resize {
+    edges: Edges,
+    initial_window_geometry: Rectangle<i32, Logical>,
+    // The grab ended, the next commit is the last one
+    is_released: bool,
+}
+
+impl ResizeState {
+    fn get(window: &Window) -> Option<Resize> {
+        window.user_data().get::<Self>().and_then(|state| *state.0.borrow())
+    }
+
+    fn set(window: &Window, resize: Option<Resize>) {
+        window.user_data().insert_if_missing(Self::default);
+        if let Some(state) = window.user_data().get::<Self>() {
+            *state.0.borrow_mut() = resize;
+        }
+    }
+}
+
+pub struct ResizeSurfaceGrab {
+    pub start_data: PointerGrabStartData<State>,
+    pub window: Window,
+    pub edges: Edges,
+    pub initial_window_geometry: Rectangle<i32, Logical>,
+}
+
+impl ResizeSurfaceGrab {
+    fn resize(&self, delta: Point<i32, Logical>) {
+        let Some(toplevel) = self.window.toplevel() else {
+            return;
+        };
+        let (min_size, max_size) = with_states(toplevel.wl_surface(), |states| {
+            let data = states.cached_state.current::<SurfaceCachedState>();
+            (data.min_size, data.max_size)
+        });
+
+        let Rectangle { loc, size } = self.initial_window_geometry;
+        let edges = self.edges;
+        let resize = |size: i32, delta: i32, start: bool, end: bool, min: i32, max: i32| {
+            let size = match (start, end) {
+                (true, _) => size - delta,
+                (_, true) => size + delta,
+                _ => size,
+            };
+            // Zero means there is no limit
+            let max = if max > 0 { max } else { i32::MAX };
+            size.clamp(min.max(1), max.max(min.max(1)))
+        };
+        let w = resize(size.w, delta.x, edges.left, edges.right, min_size.w, max_size.w);
+        let h = resize(size.h, delta.y, edges.top, edges.bottom, min_size.h, max_size.h);
+
+        let initial_window_geometry = self.initial_window_geometry;
+        let resize = Resize { edges, initial_window_geometry, is_released: false };
+        ResizeState::set(&self.window, Some(resize));
+        toplevel.with_pending_state(|state| {
+            state.states.set(ToplevelState::Resizing);
+            state.size = Some(Size::from((w, h)));
+        });
+        toplevel.send_pending_configure();
+    }
+}
+
+impl PointerGrab<State> for ResizeSurfaceGrab {
+    fn motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        _focus: Option<(WlSurface, Point<i32, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        // No client has pointer focus while the window is resized
+        handle.motion(data, None, event);
+
+        let delta = (event.location - self.start_data.location).to_i32_round();
+        self.resize(delta);
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        focus: Option<(WlSurface, Point<i32, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, focus, event);
+    }
+
+    fn button(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &ButtonEvent,
+    ) {
+        handle.button(data, event);
+        if handle.current_pressed().contains(&self.start_data.button) {
+            return;
+        }
+        handle.unset_grab(data, event.serial, event.time, true);
+        if let Some(resize) = ResizeState::get(&self.window) {
+            ResizeState::set(&self.window, Some(Resize { is_released: true, ..resize }));
+        }
+        if let Some(toplevel) = self.window.toplevel() {
+            toplevel.with_pending_state(|state| state.states.unset(ToplevelState::Resizing));
+            toplevel.send_pending_configure();
+        }
+    }
+
+    fn axis(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        details: AxisFrame,
+    ) {
+        handle.axis(data, details);
+    }
+
+    fn frame(&mut self, data: &mut State, handle: &mut PointerInnerHandle<'_, State>) {
+        handle.frame(data);
+    }
+
+    forward_gestures!();
+
+    fn start_data(&self) -> &PointerGrabStartData<State> {
+        &self.start_data
+    }
+}
+
+impl State {
+    // Dragging the left or top edge keeps the opposite one in place, which is only known once
+    // the client has committed the size it picked
+    pub fn commit_resize(&mut self, window: &Window) {
+        let Some(resize) = ResizeState::get(window) else {
+            return;
+        };
+        if resize.is_released {
+            ResizeState::set(window, None);
+        }
+        let Resize { edges, initial_window_geometry: Rectangle { loc, size }, .. } = resize;
+        if !edges.left && !edges.top {
+            return;
+        }
+        let current = window.geometry().size;
+        let x = if edges.left { loc.x + size.w - current.w } else { loc.x };
+        let y = if edges.top { loc.y + size.h - current.h } else { loc.y };
+        if let Some(workspace) = self.shell.workspaces.owner_mut(window) {
+            workspace.map_window(window.clone(), (x, y), false);
+        }
+    }
+}
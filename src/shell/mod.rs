@@ -1,21 +1,44 @@
-use std::process::Command;
+use std::process::ExitStatus;
 
+use anyhow::Result;
 use smithay::desktop::Window;
+use smithay::output::Output;
+use smithay::reexports::calloop::LoopHandle;
 pub use workspaces::Workspaces;
 
+pub use self::output::output_area;
+use self::overview::Overview;
+use self::process::Processes;
+use self::switcher::Switcher;
+use crate::backend::Backend;
+use crate::config::{Config, Spawn, WorkspaceId};
+use crate::render::element::OutputRenderElement;
+use crate::state::{CalloopData, State};
+
+mod floating;
 mod focus;
 mod fullscreen;
+mod grabs;
+mod output;
+mod overview;
+mod process;
+mod rules;
+mod switcher;
 mod workspaces;
 
 pub struct Shell {
     pub workspaces: Workspaces,
+    pub processes: Processes,
+    pub switcher: Option<Switcher>,
+    pub overview: Option<Overview>,
 }
 
 impl Shell {
-    pub fn new(workspaces_count: usize) -> Self {
-        assert!(workspaces_count > 0, "Workspaces count should be > 0");
-        let workspaces = Workspaces::new(workspaces_count);
-        Self { workspaces }
+    pub fn new(config: &Config, event_loop: LoopHandle<'static, CalloopData>) -> Self {
+        assert!(config.workspace_count > 0, "Workspaces count should be > 0");
+        let workspaces = Workspaces::new(config);
+        let processes = Processes::new(event_loop);
+        Self { workspaces, processes, switcher: None, overview: None }
     }
 
     // FIXME: self
@@ -25,30 +48,29 @@ impl Shell {
         }
     }
 
-    // FIXME: self
-    pub fn spawn(&self, command: String) {
-        std::thread::spawn(move || {
-            let mut cmd = Command::new("/bin/sh");
-            cmd.args(["-c", command.as_str()]);
-            match cmd.spawn() {
-                Ok(mut child) => {
-                    let _ = child.wait();
-                }
-                Err(err) => tracing::error!(?err),
-            }
-        });
+    pub fn spawn(&mut self, spawn: Spawn) -> Result<u32> {
+        self.processes.spawn(spawn, None)
     }
 
-    pub fn switch_to(&mut self, new: usize) {
-        assert!(new > 0, "Workspace number should be > 0");
-        self.workspaces.switch_to(new - 1);
+    pub fn spawn_with(
+        &mut self,
+        spawn: Spawn,
+        on_exit: impl FnOnce(&mut State, Option<ExitStatus>) + 'static,
+    ) -> Result<u32> {
+        self.processes.spawn(spawn, Some(Box::new(on_exit)))
     }
 
-    pub fn move_to(&mut self, window: Option<Window>, new: usize) {
-        assert!(new > 0, "Workspace number should be > 0");
-        if let Some(window) = window {
-            self.workspaces.move_to(window, new - 1);
-        }
+    pub fn switch_to(&mut self, id: &WorkspaceId) -> Result<bool> {
+        let new = self.workspaces.find_or_create(id)?;
+        self.workspaces.switch_to(new)
+    }
+
+    pub fn move_to(&mut self, window: Option<Window>, id: &WorkspaceId) -> Result<()> {
+        let Some(window) = window else {
+            return Ok(());
+        };
+        let new = self.workspaces.find_or_create(id)?;
+        self.workspaces.move_to(window, new)
     }
 
     pub fn toggle_fullscreen(&mut self, window: Option<&Window>) {
@@ -60,4 +82,25 @@ impl Shell {
             }
         }
     }
+
+    // Overlays come first, as elements are drawn front to back, and only on the active output
+    pub fn render_elements<B: Backend>(
+        &self,
+        backend: &mut B,
+        output: &Output,
+        focus: Option<&Window>,
+        config: &Config,
+    ) -> Result<Vec<OutputRenderElement<B::Renderer>>> {
+        let mut elements = vec![];
+        let is_active = self.workspaces.output.as_ref() == Some(output);
+        // The overview covers the whole output
+        if let Some(overview) = self.overview.as_ref().filter(|_| is_active) {
+            return overview.render_elements(backend, &self.workspaces, config);
+        }
+        if let Some(switcher) = self.switcher.as_ref().filter(|_| is_active) {
+            elements.extend(switcher.render_elements(backend, &self.workspaces, config)?);
+        }
+        elements.extend(self.workspaces.render_elements(backend, output, focus, config)?);
+        Ok(elements)
+    }
 }
@@ -0,0 +1,119 @@
+use std::cell::Cell;
+
+use smithay::output::{Mode, Output, Scale};
+use smithay::utils::{Logical, Point, Rectangle, Size, Transform};
+
+use crate::state::State;
+
+const TRANSFORMS: [Transform; 8] = [
+    Transform::Normal,
+    Transform::_90,
+    Transform::_180,
+    Transform::_270,
+    Transform::Flipped,
+    Transform::Flipped90,
+    Transform::Flipped180,
+    Transform::Flipped270,
+];
+
+// What the backend set up the output with, before any configuration
+struct Defaults {
+    mode: Option<Mode>,
+    transform: Transform,
+    scale: Scale,
+    // The backend may change the mode itself, it is only reset after being configured
+    is_mode_configured: Cell<bool>,
+}
+
+// Where the output is in the global space
+pub fn output_area(output: &Output) -> Option<Rectangle<i32, Logical>> {
+    let mode = output.current_mode()?;
+    let size = output.current_transform().transform_size(mode.size);
+    let size = size.to_f64().to_logical(output.current_scale().fractional_scale()).to_i32_round();
+    Some(Rectangle::from_loc_and_size(output.current_location(), size))
+}
+
+impl State {
+    pub fn add_output(&mut self, output: &Output) {
+        output.user_data().insert_if_missing(|| Defaults {
+            mode: output.current_mode(),
+            transform: output.current_transform(),
+            scale: output.current_scale(),
+            is_mode_configured: Cell::new(false),
+        });
+        self.shell.workspaces.map_output(output);
+        self.configure_outputs();
+    }
+
+    // Applies the `outputs` configuration, at startup and on every reload
+    pub fn configure_outputs(&mut self) {
+        let outputs = self.shell.workspaces.outputs().cloned().collect::<Vec<_>>();
+        for output in outputs.iter() {
+            let Some(defaults) = output.user_data().get::<Defaults>() else {
+                continue;
+            };
+            let config = self.config.outputs.get(&output.name()).cloned().unwrap_or_default();
+            let workspaces = &mut self.shell.workspaces;
+            // Settings no longer configured go back to what the backend set up
+            let mode = match config.mode {
+                Some(mode) => Some(Mode::from(mode)),
+                None if defaults.is_mode_configured.get() => defaults.mode,
+                None => None,
+            };
+            if let Some(mode) = mode {
+                output.set_preferred(mode);
+                workspaces.change_output_mode(output, mode);
+            }
+            defaults.is_mode_configured.set(config.mode.is_some());
+            let transform = config.transform.map_or(Transform::Normal, Transform::from);
+            workspaces.change_output_transform(output, compose(defaults.transform, transform));
+            let scale = config.scale.map_or(defaults.scale, Scale::Fractional);
+            workspaces.change_output_scale(output, scale);
+        }
+
+        // Outputs without a configured position are placed to the right of the ones before them
+        let mut right = 0;
+        for output in outputs.iter() {
+            let config = self.config.outputs.get(&output.name());
+            let position = config.and_then(|config| config.position).unwrap_or((right, 0));
+            self.shell.workspaces.change_output_location(output, position.into());
+            if let Some(area) = output_area(output) {
+                right = right.max(area.loc.x + area.size.w);
+            }
+        }
+    }
+}
+
+// The transform that applies `transform` first and then `base`, like the flip a backend needs
+fn compose(base: Transform, transform: Transform) -> Transform {
+    let area = Size::<i32, Logical>::from((2, 2));
+    let points = [(1, 0).into(), (0, 2).into()];
+    let apply = |transform: Transform, point: Point<i32, Logical>| {
+        transform.transform_point_in(point, &area)
+    };
+    TRANSFORMS
+        .into_iter()
+        .find(|composed| {
+            points
+                .iter()
+                .all(|&point| apply(*composed, point) == apply(base, apply(transform, point)))
+        })
+        .unwrap_or(transform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_transforms() {
+        for transform in TRANSFORMS {
+            assert_eq!(compose(Transform::Normal, transform), transform);
+            assert_eq!(compose(transform, Transform::Normal), transform);
+        }
+        assert_eq!(compose(Transform::_90, Transform::_90), Transform::_180);
+        assert_eq!(compose(Transform::_180, Transform::_270), Transform::_90);
+        assert_eq!(compose(Transform::Flipped180, Transform::Flipped180), Transform::Normal);
+        assert_eq!(compose(Transform::Flipped180, Transform::_180), Transform::Flipped);
+    }
+}
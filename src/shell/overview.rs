@@ -0,0 +1,292 @@
+use anyhow::Result;
+use smithay::backend::input::ButtonState;
+use smithay::desktop::Window;
+use smithay::output::Output;
+use smithay::utils::{Logical, Point, Rectangle, Size};
+
+use super::workspaces::{Workspace, Workspaces};
+use crate::backend::Backend;
+use crate::config::Config;
+use crate::render::element::{OutputRenderElement, RoundedElement};
+use crate::render::overlay::{self, solid, PADDING};
+use crate::state::State;
+
+const BACKDROP_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+const WORKSPACE_COLOR: [f32; 4] = [0.3, 0.3, 0.3, 1.0];
+
+// Shows all workspaces scaled down in a grid, the current one highlighted
+#[derive(Default)]
+pub struct Overview {
+    drag: Option<Drag>,
+}
+
+// A window thumbnail picked up to be dropped on another workspace. The workspace it is on is
+// looked up on drop, as indices change when workspaces are pruned meanwhile
+struct Drag {
+    window: Window,
+    // Where the thumbnail was picked up, and where the pointer was then and is now
+    thumbnail: Rectangle<i32, Logical>,
+    start: Point<f64, Logical>,
+    point: Point<f64, Logical>,
+}
+
+// Output coordinates of the workspaces in the grid and how much they are scaled down
+struct Grid {
+    area: Rectangle<i32, Logical>,
+    cells: Vec<Rectangle<i32, Logical>>,
+    scale: f64,
+}
+
+impl Grid {
+    fn new(workspaces: &Workspaces) -> Option<Self> {
+        let size = workspaces.output_geometry()?.size;
+        Some(Self::with_size(size, workspaces.count()))
+    }
+
+    fn with_size(size: Size<i32, Logical>, n: usize) -> Self {
+        let area = Rectangle::from_loc_and_size((0, 0), size);
+        let n = n as i32;
+        let columns = (n as f64).sqrt().ceil() as i32;
+        let rows = (n + columns - 1) / columns;
+        let scale = ((area.size.w - PADDING * (columns + 1)) as f64
+            / (columns * area.size.w) as f64)
+            .min((area.size.h - PADDING * (rows + 1)) as f64 / (rows * area.size.h) as f64)
+            .max(0.0);
+        let cell = area.size.to_f64().upscale(scale).to_i32_round();
+        let (_, cells) = overlay::grid(area, n as usize, cell);
+        Self { area, cells, scale }
+    }
+
+    fn workspace_at(&self, point: Point<f64, Logical>) -> Option<usize> {
+        self.cells.iter().position(|cell| cell.to_f64().contains(point))
+    }
+
+    // Where a window of a workspace is drawn, if it is in view
+    fn window_geometry(
+        &self,
+        index: usize,
+        workspace: &Workspace,
+        window: &Window,
+    ) -> Option<Rectangle<i32, Logical>> {
+        let mut geometry = workspace.space.element_geometry(window)?;
+        geometry.loc -= workspace.origin() + workspace.view_offset();
+        self.thumbnail(index, geometry)
+    }
+
+    // Scales a geometry relative to the output down into the cell of a workspace. Windows partly
+    // out of view are scaled down further and moved so as not to spill over into other cells
+    fn thumbnail(
+        &self,
+        index: usize,
+        geometry: Rectangle<i32, Logical>,
+    ) -> Option<Rectangle<i32, Logical>> {
+        let is_visible = geometry.loc.x < self.area.size.w
+            && geometry.loc.y < self.area.size.h
+            && geometry.loc.x + geometry.size.w > 0
+            && geometry.loc.y + geometry.size.h > 0;
+        if !is_visible {
+            return None;
+        }
+        let cell = self.cells.get(index)?;
+        let geometry = geometry.to_f64().upscale(self.scale);
+        let fit = (cell.size.w as f64 / geometry.size.w)
+            .min(cell.size.h as f64 / geometry.size.h)
+            .min(1.0);
+        let size = geometry.size.upscale(fit).to_i32_round::<i32>();
+        let center = cell.loc.to_f64() + geometry.loc + geometry.size.downscale(2.0).to_point();
+        let loc = center - size.to_f64().downscale(2.0).to_point();
+        let loc = Point::from((
+            (loc.x.round() as i32).clamp(cell.loc.x, cell.loc.x + cell.size.w - size.w),
+            (loc.y.round() as i32).clamp(cell.loc.y, cell.loc.y + cell.size.h - size.h),
+        ));
+        Some(Rectangle::from_loc_and_size(loc, size))
+    }
+
+    fn window_at(&self, workspaces: &Workspaces, point: Point<f64, Logical>) -> Option<Drag> {
+        let index = self.workspace_at(point)?;
+        let (_, workspace) = workspaces.iter().nth(index)?;
+        let thumbnails = workspace
+            .windows()
+            .rev()
+            .filter_map(|window| Some((window, self.window_geometry(index, workspace, window)?)));
+        let (window, thumbnail) = topmost(thumbnails, point)?;
+        Some(Drag { window: window.clone(), thumbnail, start: point, point })
+    }
+}
+
+// The first of the thumbnails, ordered top to bottom, under the point
+fn topmost<T>(
+    thumbnails: impl IntoIterator<Item = (T, Rectangle<i32, Logical>)>,
+    point: Point<f64, Logical>,
+) -> Option<(T, Rectangle<i32, Logical>)> {
+    thumbnails.into_iter().find(|(_, thumbnail)| thumbnail.to_f64().contains(point))
+}
+
+// The grid is laid out on the active output
+fn output_point(workspaces: &Workspaces, point: Point<f64, Logical>) -> Point<f64, Logical> {
+    point - workspaces.output.as_ref().map(Output::current_location).unwrap_or_default().to_f64()
+}
+
+impl Overview {
+    pub fn render_elements<B: Backend>(
+        &self,
+        backend: &mut B,
+        workspaces: &Workspaces,
+        config: &Config,
+    ) -> Result<Vec<OutputRenderElement<B::Renderer>>> {
+        let mut elements = vec![];
+        let Some((output, grid)) = workspaces.output.as_ref().zip(Grid::new(workspaces)) else {
+            return Ok(elements);
+        };
+        let output_scale = output.current_scale().fractional_scale();
+        let output_transform = output.current_transform();
+        let radius = config.outline.radius as f32 * grid.scale as f32;
+
+        // The dragged thumbnail follows the pointer, above everything else
+        let mut thumbnails = vec![];
+        if let Some(drag) = self.drag.as_ref() {
+            let loc = drag.thumbnail.loc + (drag.point - drag.start).to_i32_round();
+            let thumbnail = Rectangle::from_loc_and_size(loc, drag.thumbnail.size);
+            thumbnails.push((&drag.window, thumbnail));
+        }
+        let dragged = self.drag.as_ref().map(|drag| &drag.window);
+        for (index, workspace) in workspaces.iter() {
+            let windows = workspace.windows().rev().filter(|&window| Some(window) != dragged);
+            thumbnails.extend(windows.filter_map(|window| {
+                Some((window, grid.window_geometry(index, workspace, window)?))
+            }));
+        }
+
+        for (window, thumbnail) in thumbnails {
+            let Some(texture) = workspaces.window_texture(backend, output, window)? else {
+                continue;
+            };
+            let program = backend.renderer().outline_program();
+            let geometry = Rectangle::from_loc_and_size(thumbnail.loc, window.geometry().size);
+            let element = RoundedElement::new(
+                config.outline.color,
+                1.0,
+                geometry,
+                program,
+                radius,
+                texture,
+                output_transform,
+                0.0,
+            );
+            let element = element.with_buffer_scale(output_scale).scaled_to(thumbnail);
+            elements.push(OutputRenderElement::RoundedWindow(element));
+        }
+        for (index, &cell) in grid.cells.iter().enumerate() {
+            elements.push(solid(cell, WORKSPACE_COLOR, output_scale));
+            if index == workspaces.current_index() {
+                let [r, g, b] = config.outline.focused_color;
+                let highlight = Rectangle::from_loc_and_size(
+                    cell.loc - (PADDING / 2, PADDING / 2).into(),
+                    cell.size + Size::from((PADDING, PADDING)),
+                );
+                elements.push(solid(highlight, [r, g, b, 1.0], output_scale));
+            }
+        }
+        elements.push(solid(grid.area, BACKDROP_COLOR, output_scale));
+        Ok(elements)
+    }
+}
+
+impl State {
+    pub fn toggle_overview(&mut self) {
+        self.shell.overview = match self.shell.overview.take() {
+            Some(_) => None,
+            None => Some(Overview::default()),
+        };
+    }
+
+    pub fn overview_motion(&mut self, point: Point<f64, Logical>) {
+        let point = output_point(&self.shell.workspaces, point);
+        if let Some(drag) = self.shell.overview.as_mut().and_then(|overview| overview.drag.as_mut())
+        {
+            drag.point = point;
+        }
+    }
+
+    // Clicking a workspace switches to it, dropping a window thumbnail on one moves the window
+    pub fn overview_button(&mut self, point: Point<f64, Logical>, state: ButtonState) {
+        let workspaces = &self.shell.workspaces;
+        let Some((overview, grid)) = self.shell.overview.as_mut().zip(Grid::new(workspaces)) else {
+            return;
+        };
+        let point = output_point(workspaces, point);
+        if state == ButtonState::Pressed {
+            overview.drag = grid.window_at(workspaces, point);
+            return;
+        }
+        let drag = overview.drag.take();
+        let Some(to) = grid.workspace_at(point) else {
+            return;
+        };
+        let from = drag.as_ref().and_then(|drag| workspaces.workspace_of(&drag.window));
+        match drag.zip(from) {
+            Some((Drag { window, .. }, from)) if from != to => {
+                let is_current = from == self.shell.workspaces.current_index();
+                let is_moved = self.shell.workspaces.move_to(window, to).is_ok();
+                if is_moved && is_current {
+                    let window = self.shell.workspaces.current().windows().next().cloned();
+                    self.set_focus(window);
+                }
+            }
+            _ => {
+                self.shell.overview = None;
+                if self.shell.workspaces.switch_to(to).unwrap_or(false) {
+                    self.emit_workspace_switched();
+                }
+                let window = self.shell.workspaces.current().windows().next().cloned();
+                self.set_focus(window);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::workspaces::fixtures::rect;
+
+    #[test]
+    fn grid_cells() {
+        let grid = Grid::with_size((1000, 800).into(), 4);
+        assert_eq!(grid.area, rect(0, 0, 1000, 800));
+        assert_eq!(
+            grid.cells,
+            vec![
+                rect(22, 16, 470, 376),
+                rect(508, 16, 470, 376),
+                rect(22, 408, 470, 376),
+                rect(508, 408, 470, 376)
+            ]
+        );
+
+        assert_eq!(grid.workspace_at((30.0, 20.0).into()), Some(0));
+        assert_eq!(grid.workspace_at((600.0, 500.0).into()), Some(3));
+        // Between the cells
+        assert_eq!(grid.workspace_at((500.0, 20.0).into()), None);
+    }
+
+    #[test]
+    fn thumbnails() {
+        let grid = Grid::with_size((1000, 800).into(), 4);
+        assert_eq!(grid.thumbnail(3, rect(500, 0, 500, 800)), Some(rect(743, 408, 235, 376)));
+        // Partly out of view, kept inside the cell
+        assert_eq!(grid.thumbnail(0, rect(-100, 0, 500, 800)), Some(rect(22, 16, 235, 376)));
+        assert_eq!(grid.thumbnail(1, rect(0, 0, 2000, 800)), Some(rect(508, 110, 470, 188)));
+        // Scrolled out of view, or on a workspace that is not in the grid
+        assert_eq!(grid.thumbnail(0, rect(1000, 0, 500, 800)), None);
+        assert_eq!(grid.thumbnail(4, rect(0, 0, 500, 800)), None);
+    }
+
+    #[test]
+    fn topmost_thumbnail() {
+        let thumbnails = [(1, rect(0, 0, 100, 100)), (2, rect(50, 50, 100, 100))];
+        assert_eq!(topmost(thumbnails, (75.0, 75.0).into()), Some(thumbnails[0]));
+        assert_eq!(topmost(thumbnails, (125.0, 125.0).into()), Some(thumbnails[1]));
+        assert_eq!(topmost(thumbnails, (125.0, 25.0).into()), None);
+    }
+}
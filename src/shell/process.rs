@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::io;
+use std::os::fd::{FromRawFd, OwnedFd, RawFd};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use smithay::reexports::calloop::generic::Generic;
+use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
+use smithay::reexports::calloop::{Interest, LoopHandle, Mode, PostAction};
+use tracing::{debug, info, warn};
+
+use crate::config::{Exec, Spawn};
+use crate::state::{CalloopData, State};
+
+// How often children are checked on kernels without pidfd_open
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+type OnExit = Box<dyn FnOnce(&mut State, Option<ExitStatus>)>;
+
+struct Process {
+    child: Child,
+    on_exit: Option<OnExit>,
+}
+
+pub struct Processes {
+    event_loop: LoopHandle<'static, CalloopData>,
+    children: HashMap<u32, Process>,
+}
+
+impl Processes {
+    pub fn new(event_loop: LoopHandle<'static, CalloopData>) -> Self {
+        Self { event_loop, children: HashMap::new() }
+    }
+
+    pub fn spawn(&mut self, spawn: Spawn, on_exit: Option<OnExit>) -> Result<u32> {
+        let exec = Exec::from(spawn);
+        let mut cmd = command(&exec)?;
+        let child = cmd.spawn().with_context(|| format!("Failed to spawn {exec:?}"))?;
+        let pid = child.id();
+        debug!(pid, ?exec, "Spawned process");
+
+        self.children.insert(pid, Process { child, on_exit });
+        if let Err(err) = self.watch(pid) {
+            // An untracked child would never be reaped
+            if let Some(Process { mut child, .. }) = self.children.remove(&pid) {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            return Err(err);
+        }
+        Ok(pid)
+    }
+
+    fn watch(&self, pid: u32) -> Result<()> {
+        match pidfd_open(pid) {
+            Ok(pidfd) => {
+                let source = Generic::new(pidfd, Interest::READ, Mode::Level);
+                self.event_loop
+                    .insert_source(source, move |_, _, data| {
+                        exited(&mut data.state, pid);
+                        Ok(PostAction::Remove)
+                    })
+                    .map_err(|_| anyhow!("Failed to initialize process source"))?;
+            }
+            Err(err) => {
+                debug!(pid, ?err, "Polling process without a pidfd");
+                self.event_loop
+                    .insert_source(Timer::from_duration(POLL_INTERVAL), move |_, _, data| {
+                        if !data.state.shell.processes.has_exited(pid) {
+                            return TimeoutAction::ToDuration(POLL_INTERVAL);
+                        }
+                        exited(&mut data.state, pid);
+                        TimeoutAction::Drop
+                    })
+                    .map_err(|_| anyhow!("Failed to initialize process timer"))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn pids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.children.keys().copied()
+    }
+
+    // Children lead their own process groups, signalling the group reaches what they spawned too
+    pub fn terminate_all(&self) {
+        for pid in self.pids() {
+            info!(pid, "Terminating process group");
+            if unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGTERM) } != 0 {
+                warn!(pid, err = ?io::Error::last_os_error(), "Failed to terminate process");
+            }
+        }
+    }
+
+    fn has_exited(&mut self, pid: u32) -> bool {
+        self.children
+            .get_mut(&pid)
+            .map_or(true, |process| !matches!(process.child.try_wait(), Ok(None)))
+    }
+
+    fn reap(&mut self, pid: u32) -> Option<(Option<ExitStatus>, Option<OnExit>)> {
+        let Process { mut child, on_exit } = self.children.remove(&pid)?;
+        let status = child.wait().map_err(|err| warn!(pid, ?err, "Failed to reap process")).ok();
+        debug!(pid, ?status, "Process exited");
+        Some((status, on_exit))
+    }
+}
+
+fn exited(state: &mut State, pid: u32) {
+    if let Some((status, on_exit)) = state.shell.processes.reap(pid) {
+        if let Some(on_exit) = on_exit {
+            on_exit(state, status);
+        }
+    }
+}
+
+fn command(exec: &Exec) -> Result<Command> {
+    let mut cmd = match (exec.shell.as_ref(), exec.argv.split_first()) {
+        (Some(shell), None) => {
+            let mut cmd = Command::new("/bin/sh");
+            cmd.args(["-c", shell.as_str()]);
+            cmd
+        }
+        (None, Some((program, args))) => {
+            let mut cmd = Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+        _ => bail!("Exactly one of shell or argv should be set"),
+    };
+    cmd.envs(exec.env.iter());
+    if let Some(cwd) = exec.cwd.as_ref() {
+        cmd.current_dir(cwd);
+    }
+    // Start children in their own process group so they don't receive our signals
+    cmd.process_group(0);
+    Ok(cmd)
+}
+
+fn pidfd_open(pid: u32) -> io::Result<OwnedFd> {
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
@@ -0,0 +1,64 @@
+use smithay::desktop::Window;
+use tracing::{debug, warn};
+
+use crate::config::Color;
+use crate::ipc::query;
+use crate::state::State;
+
+// How a window is drawn, as set by window rules
+#[derive(Debug, Clone, Copy)]
+pub struct WindowStyle {
+    pub opacity: f32,
+    pub outline_color: Option<Color>,
+}
+
+impl Default for WindowStyle {
+    fn default() -> Self {
+        Self { opacity: 1.0, outline_color: None }
+    }
+}
+
+impl WindowStyle {
+    pub fn get(window: &Window) -> Self {
+        window.user_data().get::<Self>().copied().unwrap_or_default()
+    }
+
+    fn set(window: &Window, style: Self) {
+        window.user_data().insert_if_missing(|| style);
+    }
+}
+
+impl State {
+    // Clients set app_id and title after creating the toplevel, so rules are applied
+    // right before the initial configure
+    pub fn apply_rules(&mut self, window: &Window) {
+        let (app_id, title) = query::app_id_and_title(window);
+        let rule = self.config.window_rule(app_id.as_deref(), title.as_deref());
+        debug!(?app_id, ?title, ?rule, "Applying window rules");
+
+        let opacity = rule.opacity.map_or(1.0, |opacity| opacity.clamp(0.0, 1.0));
+        WindowStyle::set(window, WindowStyle { opacity, outline_color: rule.outline_color });
+
+        let workspaces = &mut self.shell.workspaces;
+        if rule.floating.is_some_and(|floating| floating != workspaces.is_floating(window)) {
+            workspaces.toggle_floating(window);
+        }
+        if workspaces.is_floating(window) {
+            workspaces.place_floating(window, rule.size, rule.position);
+        }
+        if rule.fullscreen == Some(true) {
+            workspaces.fullscreen(window);
+        }
+
+        if let Some(id) = rule.workspace.as_ref() {
+            match self.shell.move_to(Some(window.clone()), id) {
+                Ok(()) if !self.shell.workspaces.current().contains(window) => {
+                    let window = self.shell.workspaces.current().windows().next().cloned();
+                    self.set_focus(window);
+                }
+                Ok(()) => (),
+                Err(err) => warn!(?app_id, "Failed to apply window rule: {err}"),
+            }
+        }
+    }
+}
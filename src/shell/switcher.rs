@@ -0,0 +1,145 @@
+use anyhow::Result;
+use smithay::desktop::Window;
+use smithay::utils::{IsAlive, Rectangle, Size};
+
+use super::workspaces::Workspaces;
+use crate::backend::Backend;
+use crate::config::{Config, KeyModifiers};
+use crate::render::element::{OutputRenderElement, RoundedElement};
+use crate::render::overlay::{self, solid, PADDING};
+use crate::state::State;
+
+const THUMBNAIL_SIZE: (i32, i32) = (240, 180);
+const BACKDROP_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 0.8];
+
+// Open while the modifiers of the binding that opened it are held
+pub struct Switcher {
+    windows: Vec<Window>,
+    selected: usize,
+    modifiers: KeyModifiers,
+}
+
+impl Switcher {
+    fn step(&mut self, delta: isize) {
+        let len = self.windows.len() as isize;
+        self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+    }
+
+    pub fn render_elements<B: Backend>(
+        &self,
+        backend: &mut B,
+        workspaces: &Workspaces,
+        config: &Config,
+    ) -> Result<Vec<OutputRenderElement<B::Renderer>>> {
+        let mut elements = vec![];
+        let Some((output, output_geometry)) =
+            workspaces.output.as_ref().zip(workspaces.output_geometry())
+        else {
+            return Ok(elements);
+        };
+        let output_scale = output.current_scale().fractional_scale();
+        let output_transform = output.current_transform();
+        let area = Rectangle::from_loc_and_size((0, 0), output_geometry.size);
+        let (panel, cells) = overlay::grid(area, self.windows.len(), THUMBNAIL_SIZE.into());
+
+        for (index, (window, cell)) in self.windows.iter().zip(cells).enumerate() {
+            if !window.alive() {
+                continue;
+            }
+            let size = window.geometry().size;
+            let Some(texture) = workspaces.window_texture(backend, output, window)? else {
+                continue;
+            };
+            let thumbnail = overlay::fit(size, cell);
+            let scale = thumbnail.size.w as f32 / size.w.max(1) as f32;
+            let radius = config.outline.radius as f32 * scale;
+            let program = backend.renderer().outline_program();
+            let geometry = Rectangle::from_loc_and_size(thumbnail.loc, size);
+            let element = RoundedElement::new(
+                config.outline.color,
+                1.0,
+                geometry,
+                program,
+                radius,
+                texture,
+                output_transform,
+                0.0,
+            );
+            let element = element.with_buffer_scale(output_scale).scaled_to(thumbnail);
+            elements.push(OutputRenderElement::RoundedWindow(element));
+
+            if index == self.selected {
+                let [r, g, b] = config.outline.focused_color;
+                let highlight = Rectangle::from_loc_and_size(
+                    cell.loc - (PADDING / 2, PADDING / 2).into(),
+                    cell.size + Size::from((PADDING, PADDING)),
+                );
+                elements.push(solid(highlight, [r, g, b, 1.0], output_scale));
+            }
+        }
+        elements.push(solid(panel, BACKDROP_COLOR, output_scale));
+        Ok(elements)
+    }
+}
+
+impl State {
+    // Opens the switcher on the window after the focused one in most recently used order,
+    // or selects another one while it is open
+    pub fn step_switcher(&mut self, delta: isize) {
+        if let Some(switcher) = self.shell.switcher.as_mut() {
+            switcher.step(delta);
+            return;
+        }
+
+        let workspaces = &self.shell.workspaces;
+        let mut windows = self.focus_history();
+        windows.retain(|window| workspaces.workspace_of(window).is_some());
+        let unused = workspaces
+            .iter()
+            .flat_map(|(_, workspace)| workspace.windows())
+            .filter(|w| !windows.contains(w))
+            .cloned()
+            .collect::<Vec<_>>();
+        windows.extend(unused);
+        if windows.is_empty() {
+            return;
+        }
+
+        let modifiers = self.held_modifiers();
+        let is_held = modifiers.is_some();
+        let mut switcher =
+            Switcher { windows, selected: 0, modifiers: modifiers.unwrap_or_default() };
+        switcher.step(delta);
+        self.shell.switcher = Some(switcher);
+        if !is_held {
+            self.confirm_switcher();
+        }
+    }
+
+    pub fn update_switcher(&mut self) {
+        let Some(switcher) = self.shell.switcher.as_ref() else {
+            return;
+        };
+        let keyboard = self.seat.get_keyboard().unwrap();
+        if !KeyModifiers::from(keyboard.modifier_state()).contains(&switcher.modifiers) {
+            self.confirm_switcher();
+        }
+    }
+
+    // Focuses the selected window, switching to its workspace if needed
+    fn confirm_switcher(&mut self) {
+        let Some(switcher) = self.shell.switcher.take() else {
+            return;
+        };
+        let Some(window) = switcher.windows.get(switcher.selected).filter(|w| w.alive()) else {
+            return;
+        };
+        let workspaces = &mut self.shell.workspaces;
+        let to = workspaces.workspace_of(window);
+        if to.is_some_and(|to| workspaces.switch_to(to).unwrap_or(false)) {
+            self.emit_workspace_switched();
+        }
+        self.shell.workspaces.raise_floating(window);
+        self.set_focus(Some(window.clone()));
+    }
+}
@@ -0,0 +1,230 @@
+use smithay::desktop::Window;
+use smithay::utils::{Logical, Point, Rectangle};
+
+use super::{split_rows, swap_window, Tile};
+use crate::config::Tiling;
+
+pub struct Columns<W: Tile = Window> {
+    columns: Vec<Column<W>>,
+    focused: usize,
+    width: f32,
+    view_x: i32,
+}
+
+struct Column<W> {
+    windows: Vec<W>,
+    active: usize,
+    width: f32,
+}
+
+impl<W> Column<W> {
+    fn new(window: W, width: f32) -> Self {
+        Self { windows: vec![window], active: 0, width }
+    }
+
+    fn active(&self) -> Option<&W> {
+        self.windows.get(self.active)
+    }
+}
+
+impl<W: Tile> Columns<W> {
+    pub fn new(tiling: &Tiling) -> Self {
+        let width = tiling.column_width.clamp(0.1, 1.0);
+        Self { columns: Vec::new(), focused: 0, width, view_x: 0 }
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = &W> {
+        self.columns.iter().flat_map(|column| column.windows.iter())
+    }
+
+    // New windows open in a column right of the focused one
+    pub fn add_window(&mut self, window: W) {
+        if self.windows().any(|w| w == &window) {
+            return;
+        }
+        let index = if self.columns.is_empty() { 0 } else { self.focused + 1 };
+        self.columns.insert(index, Column::new(window, self.width));
+        self.focused = index;
+    }
+
+    pub fn remove_window(&mut self, window: &W) {
+        let Some((index, column)) =
+            self.columns.iter_mut().enumerate().find(|(_, c)| c.windows.contains(window))
+        else {
+            return;
+        };
+        column.windows.retain(|w| w != window);
+        column.active = column.active.min(column.windows.len().saturating_sub(1));
+        if column.windows.is_empty() {
+            self.columns.remove(index);
+            if index < self.focused {
+                self.focused -= 1;
+            }
+        }
+        self.focused = self.focused.min(self.columns.len().saturating_sub(1));
+    }
+
+    pub fn swap_windows(&mut self, a: &W, b: &W) {
+        let windows = self.columns.iter_mut().flat_map(|column| column.windows.iter_mut());
+        windows.for_each(|window| swap_window(window, a, b));
+    }
+
+    pub fn focus(&mut self, window: &W) -> bool {
+        let position = self.columns.iter().enumerate().find_map(|(index, column)| {
+            column.windows.iter().position(|w| w == window).map(|active| (index, active))
+        });
+        let Some((index, active)) = position else {
+            return false;
+        };
+        let is_changed = self.focused != index || self.columns[index].active != active;
+        self.focused = index;
+        self.columns[index].active = active;
+        is_changed
+    }
+
+    pub fn focus_column(&mut self, delta: isize) -> Option<W> {
+        let index = self.neighbour(delta)?;
+        self.focused = index;
+        self.columns[index].active().cloned()
+    }
+
+    pub fn move_column(&mut self, delta: isize) {
+        if let Some(index) = self.neighbour(delta) {
+            self.columns.swap(self.focused, index);
+            self.focused = index;
+        }
+    }
+
+    // Moves the active window of the focused column to the bottom of the column on its left
+    pub fn consume_into_column(&mut self) {
+        let Some(left) = self.focused.checked_sub(1) else {
+            return;
+        };
+        let column = &mut self.columns[self.focused];
+        if column.windows.is_empty() {
+            return;
+        }
+        let window = column.windows.remove(column.active);
+        column.active = column.active.min(column.windows.len().saturating_sub(1));
+        if column.windows.is_empty() {
+            self.columns.remove(self.focused);
+        }
+        let target = &mut self.columns[left];
+        target.windows.push(window);
+        target.active = target.windows.len() - 1;
+        self.focused = left;
+    }
+
+    // Moves the active window of the focused column into a new column on its right
+    pub fn expel_from_column(&mut self) {
+        let Some(column) = self.columns.get_mut(self.focused) else {
+            return;
+        };
+        if column.windows.len() < 2 {
+            return;
+        }
+        let window = column.windows.remove(column.active);
+        column.active = column.active.min(column.windows.len() - 1);
+        self.columns.insert(self.focused + 1, Column::new(window, self.width));
+        self.focused += 1;
+    }
+
+    pub fn arrange(&mut self, area: Rectangle<i32, Logical>) -> Vec<(W, Rectangle<i32, Logical>)> {
+        let mut geometries = vec![];
+        let mut focused = None;
+        let mut x = 0;
+        for (index, column) in self.columns.iter().enumerate() {
+            let width = (area.size.w as f32 * column.width).round() as i32;
+            if index == self.focused {
+                focused = Some((x, width));
+            }
+            let column_area =
+                Rectangle::from_loc_and_size((area.loc.x + x, area.loc.y), (width, area.size.h));
+            let tiled = column.windows.iter().filter(|w| !w.is_fullscreen());
+            let rows = split_rows(column_area, tiled.clone().count());
+            geometries.extend(tiled.cloned().zip(rows));
+            x += width;
+        }
+
+        // Scroll just enough to keep the focused column visible
+        if let Some((left, width)) = focused {
+            if left < self.view_x {
+                self.view_x = left;
+            } else if left + width > self.view_x + area.size.w {
+                self.view_x = left + width - area.size.w;
+            }
+        }
+        self.view_x = self.view_x.min(x - area.size.w).max(0);
+
+        geometries
+    }
+
+    pub fn view_offset(&self) -> Point<i32, Logical> {
+        (self.view_x, 0).into()
+    }
+
+    fn neighbour(&self, delta: isize) -> Option<usize> {
+        let index = self.focused.checked_add_signed(delta)?;
+        (index < self.columns.len() && index != self.focused).then_some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{columns, rect};
+    use super::*;
+
+    #[test]
+    fn arrange_overflowing_columns() {
+        let area = rect(100, 0, 1000, 800);
+        let mut columns = columns(&[1, 2, 3]);
+        let geometries = columns.arrange(area);
+        assert_eq!(
+            geometries,
+            vec![
+                (1, rect(100, 0, 500, 800)),
+                (2, rect(600, 0, 500, 800)),
+                (3, rect(1100, 0, 500, 800))
+            ]
+        );
+        // The last column was focused when it opened, the view scrolls to show it
+        assert_eq!(columns.view_offset(), (500, 0).into());
+
+        // The view stays put while the focused column is visible
+        assert_eq!(columns.focus_column(-1), Some(2));
+        columns.arrange(area);
+        assert_eq!(columns.view_offset(), (500, 0).into());
+        assert_eq!(columns.focus_column(-1), Some(1));
+        columns.arrange(area);
+        assert_eq!(columns.view_offset(), (0, 0).into());
+        assert_eq!(columns.focus_column(2), Some(3));
+        columns.arrange(area);
+        assert_eq!(columns.view_offset(), (500, 0).into());
+    }
+
+    #[test]
+    fn arrange_stacked_columns() {
+        let area = rect(0, 0, 1000, 800);
+        let mut columns = columns(&[1, 2, 3]);
+        columns.arrange(area);
+        columns.consume_into_column();
+        let geometries = columns.arrange(area);
+        assert_eq!(
+            geometries,
+            vec![
+                (1, rect(0, 0, 500, 800)),
+                (2, rect(500, 0, 500, 400)),
+                (3, rect(500, 400, 500, 400))
+            ]
+        );
+        // Everything fits again, the view scrolls back
+        assert_eq!(columns.view_offset(), (0, 0).into());
+
+        columns.expel_from_column();
+        columns.arrange(area);
+        assert_eq!(columns.view_offset(), (500, 0).into());
+        columns.remove_window(&3);
+        columns.arrange(area);
+        assert_eq!(columns.view_offset(), (0, 0).into());
+    }
+}
@@ -0,0 +1,273 @@
+use smithay::desktop::Window;
+use smithay::utils::{Logical, Rectangle};
+
+use super::{swap_window, Tile};
+
+pub struct Dwindle<W: Tile = Window> {
+    root: Option<Node<W>>,
+    focused: Option<W>,
+}
+
+enum Node<W> {
+    Window(W),
+    Split(Box<Split<W>>),
+}
+
+struct Split<W> {
+    direction: Direction,
+    first: Node<W>,
+    second: Node<W>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Horizontal,
+    Vertical,
+}
+
+impl Direction {
+    fn at_depth(depth: usize) -> Self {
+        if depth % 2 == 0 {
+            Self::Horizontal
+        } else {
+            Self::Vertical
+        }
+    }
+
+    fn toggled(self) -> Self {
+        match self {
+            Self::Horizontal => Self::Vertical,
+            Self::Vertical => Self::Horizontal,
+        }
+    }
+}
+
+impl<W: Tile> Default for Dwindle<W> {
+    fn default() -> Self {
+        Self { root: None, focused: None }
+    }
+}
+
+impl<W: Tile> Dwindle<W> {
+    pub fn windows(&self) -> impl Iterator<Item = &W> {
+        let mut windows = vec![];
+        if let Some(root) = self.root.as_ref() {
+            root.collect_windows(&mut windows);
+        }
+        windows.into_iter()
+    }
+
+    // Splits the focused window, or the last one if focus is elsewhere
+    pub fn add_window(&mut self, window: W) {
+        if self.windows().any(|w| w == &window) {
+            return;
+        }
+        let target = self
+            .focused
+            .clone()
+            .filter(|focused| self.windows().any(|w| w == focused))
+            .or_else(|| self.windows().last().cloned());
+        match (self.root.as_mut(), target) {
+            (Some(root), Some(target)) => {
+                root.split(&target, window.clone(), 0);
+            }
+            _ => self.root = Some(Node::Window(window.clone())),
+        }
+        self.focused = Some(window);
+    }
+
+    pub fn remove_window(&mut self, window: &W) {
+        self.root = self.root.take().and_then(|root| root.remove(window));
+    }
+
+    pub fn swap_windows(&mut self, a: &W, b: &W) {
+        if let Some(root) = self.root.as_mut() {
+            root.swap_windows(a, b);
+        }
+    }
+
+    pub fn focus(&mut self, window: &W) -> bool {
+        self.focused = Some(window.clone());
+        false
+    }
+
+    // Flips the split the focused window is directly in
+    pub fn toggle_split_direction(&mut self) {
+        if let Some((root, focused)) = self.root.as_mut().zip(self.focused.as_ref()) {
+            root.toggle_split_direction(focused);
+        }
+    }
+
+    pub fn arrange(&self, area: Rectangle<i32, Logical>) -> Vec<(W, Rectangle<i32, Logical>)> {
+        let mut geometries = vec![];
+        if let Some(root) = self.root.as_ref() {
+            root.arrange(area, &mut geometries);
+        }
+        geometries
+    }
+}
+
+impl<W: Tile> Node<W> {
+    fn collect_windows<'a>(&'a self, windows: &mut Vec<&'a W>) {
+        match self {
+            Self::Window(window) => windows.push(window),
+            Self::Split(split) => {
+                split.first.collect_windows(windows);
+                split.second.collect_windows(windows);
+            }
+        }
+    }
+
+    fn split(&mut self, target: &W, window: W, depth: usize) -> bool {
+        match self {
+            Self::Window(w) if w == target => {
+                let first = Self::Window(w.clone());
+                let second = Self::Window(window);
+                let direction = Direction::at_depth(depth);
+                *self = Self::Split(Box::new(Split { direction, first, second }));
+                true
+            }
+            Self::Window(_) => false,
+            Self::Split(split) => {
+                split.first.split(target, window.clone(), depth + 1)
+                    || split.second.split(target, window, depth + 1)
+            }
+        }
+    }
+
+    fn swap_windows(&mut self, a: &W, b: &W) {
+        match self {
+            Self::Window(window) => swap_window(window, a, b),
+            Self::Split(split) => {
+                split.first.swap_windows(a, b);
+                split.second.swap_windows(a, b);
+            }
+        }
+    }
+
+    // Returns what is left of the node, a split with one child left is replaced by it
+    fn remove(self, window: &W) -> Option<Self> {
+        match self {
+            Self::Window(ref w) if w == window => None,
+            Self::Window(_) => Some(self),
+            Self::Split(split) => {
+                let Split { direction, first, second } = *split;
+                match (first.remove(window), second.remove(window)) {
+                    (Some(first), Some(second)) => {
+                        Some(Self::Split(Box::new(Split { direction, first, second })))
+                    }
+                    (first, second) => first.or(second),
+                }
+            }
+        }
+    }
+
+    fn toggle_split_direction(&mut self, window: &W) -> bool {
+        let Self::Split(split) = self else {
+            return false;
+        };
+        let is_parent = [&split.first, &split.second]
+            .into_iter()
+            .any(|node| matches!(node, Self::Window(w) if w == window));
+        if is_parent {
+            split.direction = split.direction.toggled();
+            return true;
+        }
+        split.first.toggle_split_direction(window) || split.second.toggle_split_direction(window)
+    }
+
+    fn arrange(
+        &self,
+        area: Rectangle<i32, Logical>,
+        geometries: &mut Vec<(W, Rectangle<i32, Logical>)>,
+    ) {
+        match self {
+            Self::Window(window) if window.is_fullscreen() => (),
+            Self::Window(window) => geometries.push((window.clone(), area)),
+            Self::Split(split) => {
+                let (first, second) = split_area(area, split.direction);
+                split.first.arrange(first, geometries);
+                split.second.arrange(second, geometries);
+            }
+        }
+    }
+}
+
+fn split_area(
+    area: Rectangle<i32, Logical>,
+    direction: Direction,
+) -> (Rectangle<i32, Logical>, Rectangle<i32, Logical>) {
+    let Rectangle { loc, size } = area;
+    match direction {
+        Direction::Horizontal => {
+            let w = size.w / 2;
+            (
+                Rectangle::from_loc_and_size(loc, (w, size.h)),
+                Rectangle::from_loc_and_size((loc.x + w, loc.y), (size.w - w, size.h)),
+            )
+        }
+        Direction::Vertical => {
+            let h = size.h / 2;
+            (
+                Rectangle::from_loc_and_size(loc, (size.w, h)),
+                Rectangle::from_loc_and_size((loc.x, loc.y + h), (size.w, size.h - h)),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{dwindle, rect};
+    use super::super::shrink;
+    use super::*;
+
+    #[test]
+    fn split_direction_alternates_with_depth() {
+        let dwindle = dwindle(&[1, 2, 3, 4]);
+        let mut directions = vec![];
+        let mut node = dwindle.root.as_ref().unwrap();
+        while let Node::Split(split) = node {
+            directions.push(split.direction);
+            node = &split.second;
+        }
+        assert_eq!(directions, [Direction::Horizontal, Direction::Vertical, Direction::Horizontal]);
+        assert!(matches!(node, Node::Window(4)));
+
+        let area = rect(0, 0, 1000, 800);
+        assert_eq!(
+            dwindle.arrange(area),
+            vec![
+                (1, rect(0, 0, 500, 800)),
+                (2, rect(500, 0, 500, 400)),
+                (3, rect(500, 400, 250, 400)),
+                (4, rect(750, 400, 250, 400)),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_outlines_do_not_overlap() {
+        // Odd sizes leave the halves of every split a pixel apart in size
+        let area = rect(0, 0, 1001, 701);
+        let geometries = dwindle(&[1, 2, 3, 4, 5]).arrange(area);
+        assert_eq!(geometries.len(), 5);
+
+        // Outlines are drawn `thickness` outside of the shrunk window geometry, so neighbours need
+        // twice that between them
+        let thickness = 5;
+        let shrunk = geometries.iter().map(|&(_, geometry)| shrink(geometry, thickness));
+        let shrunk = shrunk.collect::<Vec<_>>();
+        for (i, a) in shrunk.iter().enumerate() {
+            for b in &shrunk[i + 1..] {
+                let gap = [
+                    b.loc.x - (a.loc.x + a.size.w),
+                    a.loc.x - (b.loc.x + b.size.w),
+                    b.loc.y - (a.loc.y + a.size.h),
+                    a.loc.y - (b.loc.y + b.size.h),
+                ];
+                assert!(gap.into_iter().max().unwrap() >= 2 * thickness, "{a:?} and {b:?}");
+            }
+        }
+    }
+}
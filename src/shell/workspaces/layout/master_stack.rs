@@ -0,0 +1,100 @@
+use smithay::desktop::Window;
+use smithay::utils::{Logical, Rectangle};
+
+use super::{split_rows, swap_window, Tile};
+use crate::config::Tiling;
+
+const MIN_MASTER_RATIO: f32 = 0.1;
+const MAX_MASTER_RATIO: f32 = 0.9;
+
+pub struct MasterStack<W: Tile = Window> {
+    pub ratio: f32,
+    pub count: usize,
+    windows: Vec<W>,
+}
+
+impl<W: Tile> MasterStack<W> {
+    pub fn new(tiling: &Tiling) -> Self {
+        let ratio = tiling.master_ratio.clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO);
+        Self { ratio, count: tiling.master_count, windows: Vec::new() }
+    }
+
+    pub fn adjust_ratio(&mut self, delta: f32) {
+        self.ratio = (self.ratio + delta).clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO);
+    }
+
+    pub fn adjust_count(&mut self, delta: isize) {
+        self.count = self.count.saturating_add_signed(delta);
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = &W> {
+        self.windows.iter()
+    }
+
+    pub fn add_window(&mut self, window: W) {
+        if !self.windows.contains(&window) {
+            self.windows.push(window);
+        }
+    }
+
+    pub fn remove_window(&mut self, window: &W) {
+        self.windows.retain(|w| w != window);
+    }
+
+    pub fn swap_windows(&mut self, a: &W, b: &W) {
+        self.windows.iter_mut().for_each(|window| swap_window(window, a, b));
+    }
+
+    pub fn arrange(&self, area: Rectangle<i32, Logical>) -> Vec<(W, Rectangle<i32, Logical>)> {
+        let tiled = self.windows.iter().filter(|w| !w.is_fullscreen()).cloned().collect::<Vec<_>>();
+        let geometries = self.tile(area, tiled.len());
+        tiled.into_iter().zip(geometries).collect()
+    }
+
+    fn tile(&self, area: Rectangle<i32, Logical>, n: usize) -> Vec<Rectangle<i32, Logical>> {
+        let masters = self.count.min(n);
+        let stack = n - masters;
+        if masters == 0 || stack == 0 {
+            return split_rows(area, n);
+        }
+
+        let master_width = (area.size.w as f32 * self.ratio).round() as i32;
+        let master_area = Rectangle::from_loc_and_size(area.loc, (master_width, area.size.h));
+        let stack_area = Rectangle::from_loc_and_size(
+            (area.loc.x + master_width, area.loc.y),
+            (area.size.w - master_width, area.size.h),
+        );
+
+        let mut geometries = split_rows(master_area, masters);
+        geometries.extend(split_rows(stack_area, stack));
+        geometries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{master_stack, rect};
+    use super::*;
+
+    #[test]
+    fn master_stack_arrange() {
+        let area = rect(0, 0, 1000, 900);
+        assert!(master_stack(&[]).arrange(area).is_empty());
+        assert_eq!(master_stack(&[1]).arrange(area), vec![(1, area)]);
+
+        let mut layout = master_stack(&[1, 2, 3, 4]);
+        assert_eq!(
+            layout.arrange(area),
+            vec![
+                (1, rect(0, 0, 500, 900)),
+                (2, rect(500, 0, 500, 300)),
+                (3, rect(500, 300, 500, 300)),
+                (4, rect(500, 600, 500, 300)),
+            ]
+        );
+
+        layout.adjust_count(-1);
+        layout.remove_window(&4);
+        assert_eq!(layout.arrange(area)[1], (2, rect(0, 300, 1000, 300)));
+    }
+}
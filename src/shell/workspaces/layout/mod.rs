@@ -0,0 +1,291 @@
+use smithay::desktop::Window;
+use smithay::utils::{Logical, Point, Rectangle, Size};
+use smithay::wayland::compositor::with_states;
+use smithay::wayland::shell::xdg::XdgToplevelSurfaceData;
+
+pub use self::columns::Columns;
+pub use self::dwindle::Dwindle;
+pub use self::master_stack::MasterStack;
+pub use self::tree::Tree;
+use crate::config::{Direction, LayoutKind, Tiling};
+use crate::shell::fullscreen::IsFullscreen;
+
+mod columns;
+mod dwindle;
+mod master_stack;
+mod tree;
+
+// What layouts need to know about windows, which lets them be tested without clients
+pub trait Tile: Clone + PartialEq {
+    fn is_fullscreen(&self) -> bool;
+}
+
+impl Tile for Window {
+    fn is_fullscreen(&self) -> bool {
+        IsFullscreen::get(self)
+    }
+}
+
+pub enum Layout {
+    MasterStack(MasterStack),
+    Columns(Columns),
+    Dwindle(Dwindle),
+    Tree(Tree),
+}
+
+impl Layout {
+    pub fn new(kind: LayoutKind, tiling: &Tiling) -> Self {
+        match kind {
+            LayoutKind::MasterStack => Self::MasterStack(MasterStack::new(tiling)),
+            LayoutKind::Columns => Self::Columns(Columns::new(tiling)),
+            LayoutKind::Dwindle => Self::Dwindle(Dwindle::default()),
+            LayoutKind::Tree => Self::Tree(Tree::default()),
+        }
+    }
+
+    pub fn kind(&self) -> LayoutKind {
+        match self {
+            Self::MasterStack(_) => LayoutKind::MasterStack,
+            Self::Columns(_) => LayoutKind::Columns,
+            Self::Dwindle(_) => LayoutKind::Dwindle,
+            Self::Tree(_) => LayoutKind::Tree,
+        }
+    }
+
+    pub fn set_kind(&mut self, kind: LayoutKind, tiling: &Tiling) {
+        if self.kind() == kind {
+            return;
+        }
+        let windows = self.windows();
+        *self = Self::new(kind, tiling);
+        windows.into_iter().for_each(|window| self.add_window(window));
+    }
+
+    pub fn windows(&self) -> Vec<Window> {
+        match self {
+            Self::MasterStack(layout) => layout.windows().cloned().collect(),
+            Self::Columns(layout) => layout.windows().cloned().collect(),
+            Self::Dwindle(layout) => layout.windows().cloned().collect(),
+            Self::Tree(layout) => layout.windows().cloned().collect(),
+        }
+    }
+
+    pub fn add_window(&mut self, window: Window) {
+        match self {
+            Self::MasterStack(layout) => layout.add_window(window),
+            Self::Columns(layout) => layout.add_window(window),
+            Self::Dwindle(layout) => layout.add_window(window),
+            Self::Tree(layout) => layout.add_window(window),
+        }
+    }
+
+    pub fn remove_window(&mut self, window: &Window) {
+        match self {
+            Self::MasterStack(layout) => layout.remove_window(window),
+            Self::Columns(layout) => layout.remove_window(window),
+            Self::Dwindle(layout) => layout.remove_window(window),
+            Self::Tree(layout) => layout.remove_window(window),
+        }
+    }
+
+    // The windows trade places, wherever they are in the layout
+    pub fn swap_windows(&mut self, a: &Window, b: &Window) {
+        match self {
+            Self::MasterStack(layout) => layout.swap_windows(a, b),
+            Self::Columns(layout) => layout.swap_windows(a, b),
+            Self::Dwindle(layout) => layout.swap_windows(a, b),
+            Self::Tree(layout) => layout.swap_windows(a, b),
+        }
+    }
+
+    // Returns whether the workspace should be rearranged
+    pub fn focus(&mut self, window: &Window) -> bool {
+        match self {
+            Self::MasterStack(_) => false,
+            Self::Columns(layout) => layout.focus(window),
+            Self::Dwindle(layout) => layout.focus(window),
+            Self::Tree(layout) => layout.focus(window),
+        }
+    }
+
+    pub fn arrange(
+        &mut self,
+        area: Rectangle<i32, Logical>,
+    ) -> Vec<(Window, Rectangle<i32, Logical>)> {
+        match self {
+            Self::MasterStack(layout) => layout.arrange(area),
+            Self::Columns(layout) => layout.arrange(area),
+            Self::Dwindle(layout) => layout.arrange(area),
+            Self::Tree(layout) => layout.arrange(area),
+        }
+    }
+
+    // Tab and stack headers of containers that show only their active child
+    pub fn tabs(&self) -> &[(Rectangle<i32, Logical>, bool)] {
+        match self {
+            Self::Tree(layout) => layout.tabs(),
+            _ => &[],
+        }
+    }
+
+    // A direction along a tabbed or stacked container steps to a tab of it
+    pub fn tab_in_direction(&self, direction: Direction) -> Option<Window> {
+        match self {
+            Self::Tree(layout) => layout.tab_in_direction(direction),
+            _ => None,
+        }
+    }
+
+    // Offset of the visible part of the workspace from the output location
+    pub fn view_offset(&self) -> Point<i32, Logical> {
+        match self {
+            Self::MasterStack(_) | Self::Dwindle(_) | Self::Tree(_) => Point::default(),
+            Self::Columns(layout) => layout.view_offset(),
+        }
+    }
+}
+
+fn split_columns(area: Rectangle<i32, Logical>, n: usize) -> Vec<Rectangle<i32, Logical>> {
+    let n = n as i32;
+    (0..n)
+        .map(|i| {
+            let left = area.loc.x + area.size.w * i / n;
+            let right = area.loc.x + area.size.w * (i + 1) / n;
+            Rectangle::from_loc_and_size((left, area.loc.y), (right - left, area.size.h))
+        })
+        .collect()
+}
+
+fn split_rows(area: Rectangle<i32, Logical>, n: usize) -> Vec<Rectangle<i32, Logical>> {
+    let n = n as i32;
+    (0..n)
+        .map(|i| {
+            let top = area.loc.y + area.size.h * i / n;
+            let bottom = area.loc.y + area.size.h * (i + 1) / n;
+            Rectangle::from_loc_and_size((area.loc.x, top), (area.size.w, bottom - top))
+        })
+        .collect()
+}
+
+fn swap_window<W: Tile>(window: &mut W, a: &W, b: &W) {
+    if window == a {
+        *window = b.clone();
+    } else if window == b {
+        *window = a.clone();
+    }
+}
+
+// Picks the closest geometry whose center lies in the direction from the center of `from`,
+// being off to the side counts double
+pub fn nearest_in_direction<T>(
+    from: Rectangle<i32, Logical>,
+    candidates: impl IntoIterator<Item = (T, Rectangle<i32, Logical>)>,
+    direction: Direction,
+) -> Option<T> {
+    // Doubled to stay integral
+    let center = |r: Rectangle<i32, Logical>| (r.loc.x * 2 + r.size.w, r.loc.y * 2 + r.size.h);
+    let (x, y) = center(from);
+    candidates
+        .into_iter()
+        .filter_map(|(candidate, geometry)| {
+            let (cx, cy) = center(geometry);
+            let (along, across) = match direction {
+                Direction::Left => (x - cx, cy - y),
+                Direction::Right => (cx - x, cy - y),
+                Direction::Up => (y - cy, cx - x),
+                Direction::Down => (cy - y, cx - x),
+            };
+            (along > 0).then_some((candidate, along + across.abs() * 2))
+        })
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
+
+// Leaves room for the outline, which is drawn outside of the window geometry
+pub fn shrink(geometry: Rectangle<i32, Logical>, by: i32) -> Rectangle<i32, Logical> {
+    let size = ((geometry.size.w - by * 2).max(1), (geometry.size.h - by * 2).max(1));
+    Rectangle::from_loc_and_size(geometry.loc + (by, by).into(), size)
+}
+
+pub fn configure(window: &Window, size: Size<i32, Logical>) {
+    let Some(toplevel) = window.toplevel() else {
+        return;
+    };
+    toplevel.with_pending_state(|state| state.size = Some(size));
+    let initial_configure_sent = with_states(toplevel.wl_surface(), |states| {
+        states
+            .data_map
+            .get::<XdgToplevelSurfaceData>()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .initial_configure_sent
+    });
+    // Otherwise pending state is sent with the initial configure on first commit
+    if initial_configure_sent {
+        toplevel.send_pending_configure();
+    }
+}
+
+#[cfg(test)]
+impl Tile for u32 {
+    fn is_fullscreen(&self) -> bool {
+        false
+    }
+}
+
+// Layouts of numbered windows and a shorthand for geometries, shared by the tests
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::*;
+
+    pub(crate) fn rect(x: i32, y: i32, w: i32, h: i32) -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((x, y), (w, h))
+    }
+
+    pub(crate) fn master_stack(windows: &[u32]) -> MasterStack<u32> {
+        let tiling = Tiling { master_ratio: 0.5, master_count: 1, ..Default::default() };
+        let mut layout = MasterStack::new(&tiling);
+        windows.iter().for_each(|&window| layout.add_window(window));
+        layout
+    }
+
+    pub(crate) fn columns(windows: &[u32]) -> Columns<u32> {
+        let mut columns = Columns::new(&Tiling { column_width: 0.5, ..Default::default() });
+        windows.iter().for_each(|&window| columns.add_window(window));
+        columns
+    }
+
+    pub(crate) fn dwindle(windows: &[u32]) -> Dwindle<u32> {
+        let mut dwindle = Dwindle::default();
+        windows.iter().for_each(|&window| dwindle.add_window(window));
+        dwindle
+    }
+
+    pub(crate) fn tree(windows: &[u32]) -> Tree<u32> {
+        let mut tree = Tree::default();
+        windows.iter().for_each(|&window| tree.add_window(window));
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_in_direction_prefers_aligned() {
+        // A master window on the left and a stack of three on the right
+        let area = Rectangle::from_loc_and_size((0, 0), (1000, 900));
+        let master = Rectangle::from_loc_and_size((0, 0), (500, 900));
+        let stack = split_rows(Rectangle::from_loc_and_size((500, 0), (500, 900)), 3);
+        let candidates = || stack.iter().copied().enumerate();
+
+        assert_eq!(nearest_in_direction(master, candidates(), Direction::Right), Some(1));
+        assert_eq!(nearest_in_direction(master, candidates(), Direction::Left), None);
+        assert_eq!(nearest_in_direction(stack[0], candidates(), Direction::Down), Some(1));
+        assert_eq!(nearest_in_direction(stack[2], candidates(), Direction::Up), Some(1));
+        assert_eq!(nearest_in_direction(stack[2], [(0, master)], Direction::Left), Some(0));
+        assert_eq!(nearest_in_direction(area, [(0, master)], Direction::Up), None);
+    }
+}
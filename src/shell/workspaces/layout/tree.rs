@@ -0,0 +1,477 @@
+use smithay::desktop::Window;
+use smithay::utils::{Logical, Rectangle};
+
+use super::{split_columns, split_rows, swap_window, Tile};
+use crate::config::{ContainerLayout, Direction};
+
+// Height of a tab or of one entry of a stack
+const TAB_HEIGHT: i32 = 24;
+
+pub struct Tree<W: Tile = Window> {
+    root: Container<W>,
+    focused: Option<W>,
+    // How many levels above the focused window the selection is
+    depth: usize,
+    tabs: Vec<(Rectangle<i32, Logical>, bool)>,
+}
+
+struct Container<W> {
+    layout: ContainerLayout,
+    children: Vec<Node<W>>,
+    active: usize,
+}
+
+enum Node<W> {
+    Window(W),
+    Container(Container<W>),
+}
+
+impl<W: Tile> Default for Tree<W> {
+    fn default() -> Self {
+        let root = Container::new(ContainerLayout::SplitH);
+        Self { root, focused: None, depth: 0, tabs: Vec::new() }
+    }
+}
+
+impl<W: Tile> Tree<W> {
+    pub fn windows(&self) -> impl Iterator<Item = &W> {
+        let mut windows = vec![];
+        self.root.collect_windows(&mut windows);
+        windows.into_iter()
+    }
+
+    // New windows open next to the selection
+    pub fn add_window(&mut self, window: W) {
+        if self.windows().any(|w| w == &window) {
+            return;
+        }
+        match self.selected_path().as_deref() {
+            Some([parent @ .., index]) => {
+                let parent = self.root.container_mut(parent).unwrap();
+                parent.children.insert(index + 1, Node::Window(window.clone()));
+                parent.active = index + 1;
+            }
+            _ => {
+                self.root.children.push(Node::Window(window.clone()));
+                self.root.active = self.root.children.len() - 1;
+            }
+        }
+        self.focused = Some(window);
+        self.depth = 0;
+    }
+
+    // Containers left empty are removed as well
+    pub fn remove_window(&mut self, window: &W) {
+        let Some(mut path) = self.root.path_of(window) else {
+            return;
+        };
+        while let Some(index) = path.pop() {
+            let parent = self.root.container_mut(&path).unwrap();
+            parent.children.remove(index);
+            if index < parent.active {
+                parent.active -= 1;
+            }
+            parent.active = parent.active.min(parent.children.len().saturating_sub(1));
+            if !parent.children.is_empty() {
+                break;
+            }
+        }
+        if self.focused.as_ref() == Some(window) {
+            self.focused = None;
+            self.depth = 0;
+        }
+    }
+
+    pub fn swap_windows(&mut self, a: &W, b: &W) {
+        self.root.swap_windows(a, b);
+    }
+
+    // Returns whether a tabbed or stacked container now shows another child
+    pub fn focus(&mut self, window: &W) -> bool {
+        self.focused = Some(window.clone());
+        self.depth = 0;
+        let Some(path) = self.root.path_of(window) else {
+            return false;
+        };
+        let mut is_changed = false;
+        for i in 0..path.len() {
+            let container = self.root.container_mut(&path[..i]).unwrap();
+            is_changed |= container.active != path[i] && !container.layout.shows_all();
+            container.active = path[i];
+        }
+        is_changed
+    }
+
+    pub fn focus_parent(&mut self) {
+        let len = self.focused.as_ref().and_then(|w| self.root.path_of(w)).map_or(0, |p| p.len());
+        self.depth = (self.depth + 1).min(len);
+    }
+
+    pub fn focus_child(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    // Wraps the selection into a new container, or changes the layout of a container
+    // it is the only child of
+    pub fn split(&mut self, layout: ContainerLayout) {
+        let Some(path) = self.selected_path() else {
+            return;
+        };
+        let Some((index, parent)) = path.split_last() else {
+            self.root.layout = layout;
+            return;
+        };
+        let parent = self.root.container_mut(parent).unwrap();
+        if parent.children.len() == 1 {
+            parent.layout = layout;
+            return;
+        }
+        let node = parent.children.remove(*index);
+        let container = Container { layout, children: vec![node], active: 0 };
+        parent.children.insert(*index, Node::Container(container));
+    }
+
+    // Changes the layout of the selected container, or of the one a window is in
+    pub fn set_container_layout(&mut self, layout: ContainerLayout) {
+        let Some(mut path) = self.selected_path() else {
+            return;
+        };
+        if self.root.container_mut(&path).is_none() {
+            path.pop();
+        }
+        if let Some(container) = self.root.container_mut(&path) {
+            container.layout = layout;
+        }
+    }
+
+    // Swaps the selection with its neighbour, or moves it out of its container
+    // when there is none in that direction
+    pub fn move_window(&mut self, direction: Direction) {
+        let Some(mut path) = self.selected_path() else {
+            return;
+        };
+        let Some(index) = path.pop() else {
+            return;
+        };
+        let is_forward = matches!(direction, Direction::Right | Direction::Down);
+        let is_horizontal = matches!(direction, Direction::Left | Direction::Right);
+
+        let parent = self.root.container_mut(&path).unwrap();
+        let is_parallel = parent.layout.is_horizontal() == is_horizontal;
+        if is_parallel {
+            let neighbour = if is_forward { index.checked_add(1) } else { index.checked_sub(1) };
+            if let Some(neighbour) = neighbour.filter(|&n| n < parent.children.len()) {
+                parent.children.swap(index, neighbour);
+                parent.active = neighbour;
+                return;
+            }
+            if path.is_empty() {
+                return;
+            }
+        }
+
+        let node = parent.children.remove(index);
+        parent.active = parent.active.min(parent.children.len().saturating_sub(1));
+        let is_empty = parent.children.is_empty();
+        match path.pop() {
+            // The parent is left empty, so the selection just takes its place
+            Some(parent_index) if is_empty => {
+                let grandparent = self.root.container_mut(&path).unwrap();
+                grandparent.children[parent_index] = node;
+            }
+            Some(parent_index) => {
+                let grandparent = self.root.container_mut(&path).unwrap();
+                let index = if is_forward { parent_index + 1 } else { parent_index };
+                grandparent.children.insert(index, node);
+                grandparent.active = index;
+            }
+            // Without a grandparent the root is wrapped into a container in that direction
+            None => {
+                let layout =
+                    if is_horizontal { ContainerLayout::SplitH } else { ContainerLayout::SplitV };
+                let old = std::mem::replace(&mut self.root, Container::new(layout));
+                if !old.children.is_empty() {
+                    self.root.children.push(Node::Container(old));
+                }
+                let index = if is_forward { self.root.children.len() } else { 0 };
+                self.root.children.insert(index, node);
+                self.root.active = index;
+            }
+        }
+    }
+
+    // Steps to the neighbouring tab of the innermost tabbed or stacked container along
+    // the direction, unless a split container has a neighbour there first
+    pub fn tab_in_direction(&self, direction: Direction) -> Option<W> {
+        let path = self.root.path_of(self.focused.as_ref()?)?;
+        let is_forward = matches!(direction, Direction::Right | Direction::Down);
+        let is_horizontal = matches!(direction, Direction::Left | Direction::Right);
+        for depth in (0..path.len()).rev() {
+            let container = self.root.container(&path[..depth])?;
+            if container.layout.is_horizontal() != is_horizontal {
+                continue;
+            }
+            let index = path[depth];
+            let neighbour = if is_forward { index.checked_add(1) } else { index.checked_sub(1) };
+            match neighbour.and_then(|neighbour| container.children.get(neighbour)) {
+                Some(_) if container.layout.shows_all() => return None,
+                Some(child) => return child.active_window().cloned(),
+                None => (),
+            }
+        }
+        None
+    }
+
+    pub fn arrange(&mut self, area: Rectangle<i32, Logical>) -> Vec<(W, Rectangle<i32, Logical>)> {
+        let mut geometries = vec![];
+        self.tabs.clear();
+        self.root.arrange(area, &mut geometries, &mut self.tabs);
+        geometries
+    }
+
+    // Headers of the last arrangement, and whether they belong to the active child
+    pub fn tabs(&self) -> &[(Rectangle<i32, Logical>, bool)] {
+        &self.tabs
+    }
+
+    fn selected_path(&self) -> Option<Vec<usize>> {
+        let mut path = self.root.path_of(self.focused.as_ref()?)?;
+        path.truncate(path.len().saturating_sub(self.depth));
+        Some(path)
+    }
+}
+
+impl<W: Tile> Container<W> {
+    fn new(layout: ContainerLayout) -> Self {
+        Self { layout, children: Vec::new(), active: 0 }
+    }
+
+    fn collect_windows<'a>(&'a self, windows: &mut Vec<&'a W>) {
+        for child in self.children.iter() {
+            match child {
+                Node::Window(window) => windows.push(window),
+                Node::Container(container) => container.collect_windows(windows),
+            }
+        }
+    }
+
+    fn swap_windows(&mut self, a: &W, b: &W) {
+        for child in self.children.iter_mut() {
+            match child {
+                Node::Window(window) => swap_window(window, a, b),
+                Node::Container(container) => container.swap_windows(a, b),
+            }
+        }
+    }
+
+    fn path_of(&self, window: &W) -> Option<Vec<usize>> {
+        self.children.iter().enumerate().find_map(|(index, child)| match child {
+            Node::Window(w) => (w == window).then(|| vec![index]),
+            Node::Container(container) => container.path_of(window).map(|mut path| {
+                path.insert(0, index);
+                path
+            }),
+        })
+    }
+
+    fn container(&self, path: &[usize]) -> Option<&Container<W>> {
+        match path {
+            [] => Some(self),
+            [index, rest @ ..] => match self.children.get(*index)? {
+                Node::Container(container) => container.container(rest),
+                Node::Window(_) => None,
+            },
+        }
+    }
+
+    fn container_mut(&mut self, path: &[usize]) -> Option<&mut Container<W>> {
+        match path {
+            [] => Some(self),
+            [index, rest @ ..] => match self.children.get_mut(*index)? {
+                Node::Container(container) => container.container_mut(rest),
+                Node::Window(_) => None,
+            },
+        }
+    }
+
+    // Tabbed and stacked containers show only their active child, below a row of tabs
+    // or a stack of headers, one for each child
+    fn arrange(
+        &self,
+        area: Rectangle<i32, Logical>,
+        geometries: &mut Vec<(W, Rectangle<i32, Logical>)>,
+        tabs: &mut Vec<(Rectangle<i32, Logical>, bool)>,
+    ) {
+        let n = self.children.len();
+        let (headers, areas) = match self.layout {
+            ContainerLayout::SplitH => (vec![], split_columns(area, n)),
+            ContainerLayout::SplitV => (vec![], split_rows(area, n)),
+            ContainerLayout::Tabbed => {
+                let (header, content) = split_header(area, TAB_HEIGHT);
+                (split_columns(header, n), vec![content; n])
+            }
+            ContainerLayout::Stacked => {
+                let (header, content) = split_header(area, TAB_HEIGHT * n as i32);
+                (split_rows(header, n), vec![content; n])
+            }
+        };
+        tabs.extend(
+            headers.into_iter().enumerate().map(|(index, tab)| (tab, index == self.active)),
+        );
+        for (index, (child, area)) in self.children.iter().zip(areas).enumerate() {
+            if !self.layout.shows_all() && index != self.active {
+                continue;
+            }
+            match child {
+                Node::Window(window) if window.is_fullscreen() => (),
+                Node::Window(window) => geometries.push((window.clone(), area)),
+                Node::Container(container) => container.arrange(area, geometries, tabs),
+            }
+        }
+    }
+}
+
+impl<W> Node<W> {
+    // The window a tab shows, following the active children down
+    fn active_window(&self) -> Option<&W> {
+        match self {
+            Self::Window(window) => Some(window),
+            Self::Container(container) => container.children.get(container.active)?.active_window(),
+        }
+    }
+}
+
+// Headers take at most half of the area
+fn split_header(
+    area: Rectangle<i32, Logical>,
+    height: i32,
+) -> (Rectangle<i32, Logical>, Rectangle<i32, Logical>) {
+    let height = height.min(area.size.h / 2);
+    let header = Rectangle::from_loc_and_size(area.loc, (area.size.w, height));
+    let content = Rectangle::from_loc_and_size(
+        (area.loc.x, area.loc.y + height),
+        (area.size.w, area.size.h - height),
+    );
+    (header, content)
+}
+
+impl ContainerLayout {
+    fn is_horizontal(self) -> bool {
+        matches!(self, Self::SplitH | Self::Tabbed)
+    }
+
+    fn shows_all(self) -> bool {
+        matches!(self, Self::SplitH | Self::SplitV)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{rect, tree};
+    use super::*;
+
+    fn windows(tree: &Tree<u32>) -> Vec<u32> {
+        tree.windows().copied().collect()
+    }
+
+    #[test]
+    fn remove_window_removes_empty_containers() {
+        let mut tree = tree(&[1, 2]);
+        tree.split(ContainerLayout::SplitV);
+        tree.add_window(3);
+        assert_eq!(tree.root.children.len(), 2);
+
+        tree.remove_window(&2);
+        assert!(matches!(&tree.root.children[1], Node::Container(c) if c.children.len() == 1));
+        tree.remove_window(&3);
+        assert_eq!(tree.root.children.len(), 1);
+        assert!(matches!(tree.root.children[0], Node::Window(1)));
+        assert_eq!(tree.root.active, 0);
+    }
+
+    #[test]
+    fn move_window_out_of_container() {
+        let mut tree = tree(&[1, 2]);
+        tree.split(ContainerLayout::SplitV);
+        tree.add_window(3);
+
+        // Left is across the vertical container, so the window leaves it
+        tree.move_window(Direction::Left);
+        assert_eq!(windows(&tree), [1, 3, 2]);
+        assert_eq!(tree.root.children.len(), 3);
+        tree.move_window(Direction::Right);
+        assert_eq!(windows(&tree), [1, 2, 3]);
+
+        // Without a grandparent the root is wrapped
+        tree.move_window(Direction::Up);
+        assert_eq!(tree.root.layout, ContainerLayout::SplitV);
+        assert_eq!(tree.root.children.len(), 2);
+        assert_eq!(windows(&tree), [3, 1, 2]);
+        let area = rect(0, 0, 1000, 800);
+        let geometries = tree.arrange(area);
+        assert!(geometries.contains(&(3, rect(0, 0, 1000, 400))));
+
+        // Nothing is above it any more
+        tree.move_window(Direction::Up);
+        assert_eq!(windows(&tree), [3, 1, 2]);
+    }
+
+    #[test]
+    fn split_and_focus_parent() {
+        let mut tree = tree(&[1, 2, 3]);
+        tree.focus(&2);
+        tree.split(ContainerLayout::SplitV);
+        tree.add_window(4);
+        assert_eq!(windows(&tree), [1, 2, 4, 3]);
+
+        // The selected container is wrapped as a whole
+        tree.focus_parent();
+        tree.split(ContainerLayout::Tabbed);
+        let Node::Container(tabbed) = &tree.root.children[1] else {
+            panic!("Expected a container");
+        };
+        assert_eq!(tabbed.layout, ContainerLayout::Tabbed);
+        assert!(matches!(&tabbed.children[..], [Node::Container(c)] if c.children.len() == 2));
+
+        // The selection stops at the root
+        (0..5).for_each(|_| tree.focus_parent());
+        tree.split(ContainerLayout::SplitV);
+        assert_eq!(tree.root.layout, ContainerLayout::SplitV);
+        assert_eq!(tree.root.children.len(), 3);
+
+        tree.focus_child();
+        assert_eq!(tree.selected_path(), Some(vec![1]));
+    }
+
+    #[test]
+    fn tabbed_and_stacked() {
+        let area = rect(0, 0, 900, 600);
+        let mut tree = tree(&[1, 2, 3]);
+        tree.set_container_layout(ContainerLayout::Tabbed);
+
+        let geometries = tree.arrange(area);
+        let content = rect(0, TAB_HEIGHT, 900, 600 - TAB_HEIGHT);
+        assert_eq!(geometries, [(3, content)]);
+        assert_eq!(
+            tree.tabs(),
+            [
+                (rect(0, 0, 300, TAB_HEIGHT), false),
+                (rect(300, 0, 300, TAB_HEIGHT), false),
+                (rect(600, 0, 300, TAB_HEIGHT), true),
+            ]
+        );
+        assert_eq!(tree.tab_in_direction(Direction::Left), Some(2));
+        assert_eq!(tree.tab_in_direction(Direction::Right), None);
+        assert_eq!(tree.tab_in_direction(Direction::Up), None);
+        assert!(tree.focus(&1));
+
+        // Stacked headers are rows and the stack is stepped through vertically
+        tree.set_container_layout(ContainerLayout::Stacked);
+        let geometries = tree.arrange(area);
+        let content = rect(0, TAB_HEIGHT * 3, 900, 600 - TAB_HEIGHT * 3);
+        assert_eq!(geometries, [(1, content)]);
+        assert_eq!(tree.tabs()[1].0, rect(0, TAB_HEIGHT, 900, TAB_HEIGHT));
+        assert_eq!(tree.tab_in_direction(Direction::Down), Some(2));
+        assert_eq!(tree.tab_in_direction(Direction::Left), None);
+    }
+}
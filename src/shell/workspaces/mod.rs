@@ -1,58 +1,358 @@
+use std::cell::RefCell;
 use std::time::Duration;
 
-use anyhow::Result;
-use smithay::backend::renderer::element::{surface, Kind};
-use smithay::backend::renderer::gles::GlesRenderer;
+use anyhow::{ensure, Result};
+use smithay::backend::renderer::element::{surface, Element, Id, Kind};
+use smithay::backend::renderer::utils::CommitCounter;
+use smithay::backend::renderer::Renderer;
 use smithay::desktop::{PopupManager, Window};
 use smithay::output::{Mode, Output, Scale};
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::utils::{Logical, Physical, Point, Rectangle, Transform};
+use smithay::utils::{Buffer, Logical, Physical, Point, Rectangle, Size, Transform};
 
-use self::workspace::Workspace;
+#[cfg(test)]
+pub(crate) use self::layout::fixtures;
+use self::layout::{Columns, Layout, Tree};
+pub use self::workspace::Workspace;
+use super::floating::IsFloating;
 use super::fullscreen::{GeometryBeforeFullscreen, IsFullscreen};
+use super::output::output_area;
+use super::rules::WindowStyle;
 use crate::backend::Backend;
-use crate::config::Config;
+use crate::config::{
+    Config, ContainerLayout, Direction, LayoutKind, Tiling, WorkspaceId, WorkspaceStep,
+};
 use crate::render::element::{OutputRenderElement, RoundedElement};
-use crate::render::shader::OutlineShader;
+use crate::render::overlay::solid;
+use crate::render::OutlineRenderer;
 
+mod layout;
 mod workspace;
 
+// The texture a window was last rendered into offscreen, with what it was rendered from
+struct WindowTexture<T>(RefCell<Option<(TextureKey, T)>>);
+
+#[derive(PartialEq)]
+struct TextureKey {
+    commits: Vec<(Id, CommitCounter)>,
+    size: Size<i32, Buffer>,
+    location: Point<i32, Physical>,
+}
+
+impl<T: Clone + 'static> WindowTexture<T> {
+    fn get(window: &Window, key: &TextureKey) -> Option<T> {
+        let cache = window.user_data().get::<Self>()?.0.borrow();
+        cache.as_ref().filter(|(k, _)| k == key).map(|(_, texture)| texture.clone())
+    }
+
+    fn set(window: &Window, key: TextureKey, texture: T) {
+        window.user_data().insert_if_missing(|| Self(RefCell::default()));
+        if let Some(cache) = window.user_data().get::<Self>() {
+            *cache.0.borrow_mut() = Some((key, texture));
+        }
+    }
+}
+
 pub struct Workspaces {
     current: usize,
+    // The workspace that was current before, for switching back and forth
+    previous: Option<usize>,
+    gap: i32,
+    tiling: Tiling,
+    // The output with focus, which shows the current workspace
     pub output: Option<Output>,
+    // Every output shows another workspace
+    outputs: Vec<Output>,
     workspaces: Vec<Workspace>,
+    // The first ones are kept, the others are removed when empty and not current
+    persistent: usize,
+    // Hidden unless toggled, then shown above whichever workspace is current
+    scratchpad: Workspace,
+    is_scratchpad_shown: bool,
 }
 
 impl Workspaces {
-    pub fn new(n: usize) -> Self {
+    pub fn new(config: &Config) -> Self {
         let output = None;
-        let mut workspaces = Vec::new();
-        workspaces.resize_with(n, Default::default);
-        Self { current: 0, output, workspaces }
+        let gap = config.outline.thickness as i32;
+        let tiling = config.tiling.clone();
+        let names = &config.workspace_names;
+        let persistent = config.workspace_count.max(names.len());
+        let workspaces =
+            (0..persistent).map(|i| Workspace::new(&tiling, names.get(i).cloned())).collect();
+        let scratchpad = Workspace::new(&tiling, None);
+        Self {
+            current: 0,
+            previous: None,
+            gap,
+            tiling,
+            output,
+            outputs: vec![],
+            workspaces,
+            persistent,
+            scratchpad,
+            is_scratchpad_shown: false,
+        }
+    }
+
+    // Applies a reloaded configuration, existing workspaces keep their layouts
+    pub fn reconfigure(&mut self, config: &Config) {
+        let names = &config.workspace_names;
+        let persistent = config.workspace_count.max(names.len());
+        // Workspaces created on demand keep their names unless a configured one replaces them
+        for (index, workspace) in self.workspaces.iter_mut().enumerate() {
+            if index < self.persistent || index < names.len() {
+                workspace.name = names.get(index).cloned();
+            }
+        }
+        self.tiling = config.tiling.clone();
+        while self.workspaces.len() < persistent {
+            let name = names.get(self.workspaces.len()).cloned();
+            self.workspaces.push(Workspace::new(&self.tiling, name));
+        }
+        self.persistent = persistent;
+        self.prune();
+
+        self.gap = config.outline.thickness as i32;
+        for index in 0..self.workspaces.len() {
+            if self.workspaces[index].output().is_some() {
+                self.arrange_workspace(index);
+            }
+        }
     }
 
-    pub fn switch_to(&mut self, new: usize) {
-        if let Some(output) = self.output.as_ref() {
-            let old = self.current;
-            let old_loc = self.workspaces[old].output_geometry(output).unwrap().loc;
-            self.current = new;
-            self.workspaces[old].unmap_output(output);
-            self.workspaces[new].map_output(output, old_loc);
+    // The number one past the last workspace and unknown names create a new one
+    pub fn find_or_create(&mut self, id: &WorkspaceId) -> Result<usize> {
+        let count = self.workspaces.len();
+        let (index, name) = match id {
+            WorkspaceId::Index(n) => {
+                ensure!(
+                    (1..=count + 1).contains(n),
+                    "Workspace number should be in 1..={}, got {n}",
+                    count + 1
+                );
+                (n - 1, None)
+            }
+            WorkspaceId::Name(name) => {
+                let index = self.workspaces.iter().position(|w| w.name.as_ref() == Some(name));
+                (index.unwrap_or(count), Some(name.clone()))
+            }
+        };
+        if index == count {
+            self.workspaces.push(Workspace::new(&self.tiling, name));
         }
+        Ok(index)
     }
 
-    pub fn move_to(&mut self, window: Window, new: usize) {
-        self.current_mut().unmap_window(&window);
-        self.workspaces[new].map_window(window, Point::default(), false);
+    // Steps from the current workspace by `delta`, None if there is no workspace to step to
+    pub fn relative(&self, delta: isize, step: WorkspaceStep) -> Option<usize> {
+        let count = self.workspaces.len() as isize;
+        let mut index = self.current as isize;
+        for _ in 1..count {
+            index += delta;
+            if step.wrap {
+                index = index.rem_euclid(count);
+            } else if !(0..count).contains(&index) {
+                return None;
+            }
+            if !step.skip_empty || self.workspaces[index as usize].windows().next().is_some() {
+                return Some(index as usize);
+            }
+        }
+        None
+    }
+
+    pub fn previous_index(&self) -> Option<usize> {
+        self.previous
     }
 
-    pub fn fullscreen(&self, window: &Window) -> Option<()> {
+    // Removes the workspaces that are neither persistent, current, shown nor have any windows
+    fn prune(&mut self) {
+        let is_kept = self
+            .workspaces
+            .iter()
+            .enumerate()
+            .map(|(index, workspace)| {
+                index < self.persistent
+                    || index == self.current
+                    || workspace.output().is_some()
+                    || workspace.windows().next().is_some()
+            })
+            .collect::<Vec<_>>();
+        let new_index = |index: usize| {
+            is_kept[index].then(|| is_kept[..index].iter().filter(|&&is_kept| is_kept).count())
+        };
+        self.current = new_index(self.current).unwrap_or_default();
+        self.previous = self.previous.and_then(new_index);
+        let mut is_kept = is_kept.iter();
+        self.workspaces.retain(|_| *is_kept.next().unwrap());
+    }
+
+    pub fn add_window(&mut self, window: Window) {
+        self.current_mut().add_window(window, true);
+        self.arrange();
+    }
+
+    // Returns whether the current workspace changed
+    pub fn switch_to(&mut self, new: usize) -> Result<bool> {
+        self.check_index(new)?;
+        Ok(self.activate(new))
+    }
+
+    // A workspace shown on another output is switched to by activating that output
+    fn activate(&mut self, new: usize) -> bool {
+        let old = self.current;
+        if old != new {
+            self.previous = Some(old);
+        }
+        self.current = new;
+        if let Some(output) = self.workspaces[new].output().cloned() {
+            self.set_output(output);
+        } else if let Some(output) = self.output.clone() {
+            self.workspaces[old].unmap_output(&output);
+            self.workspaces[new].map_output(&output);
+            self.arrange_workspace(new);
+        }
+        self.prune();
+        old != new
+    }
+
+    pub fn move_to(&mut self, window: Window, new: usize) -> Result<()> {
+        self.check_index(new)?;
+        let Some(old) = self.workspace_of(&window).filter(|&old| old != new) else {
+            return Ok(());
+        };
+        let location = self.workspaces[old].space.element_location(&window).unwrap_or_default();
+        self.workspaces[old].unmap_window(&window);
+        if IsFloating::get(&window) {
+            self.workspaces[new].map_window(window, location, false);
+        } else {
+            self.workspaces[new].add_window(window, false);
+        }
+        self.arrange_workspace(old);
+        self.arrange_workspace(new);
+        self.prune();
+        Ok(())
+    }
+
+    fn check_index(&self, index: usize) -> Result<()> {
+        let count = self.workspaces.len();
+        ensure!(index < count, "Workspace index should be below {count}, got {index}");
+        Ok(())
+    }
+
+    // Windows already in the scratchpad are put back into the current workspace
+    pub fn move_to_scratchpad(&mut self, window: &Window) {
+        if self.scratchpad.contains(window) {
+            self.scratchpad.unmap_window(window);
+            IsFloating::set(window, false);
+            self.add_window(window.clone());
+            return;
+        }
+        if IsFullscreen::get(window) {
+            self.unfullscreen(window);
+        }
+        let Some(index) = self.workspace_of(window) else {
+            return;
+        };
+        self.workspaces[index].unmap_window(window);
+        IsFloating::set(window, true);
+        self.scratchpad.map_window(window.clone(), Point::default(), false);
+        self.arrange_workspace(index);
+        self.prune();
+        if self.is_scratchpad_shown {
+            self.show_scratchpad();
+        }
+    }
+
+    // Returns the window to focus when the scratchpad is shown
+    pub fn toggle_scratchpad(&mut self) -> Option<Window> {
+        self.is_scratchpad_shown = !self.is_scratchpad_shown;
+        if self.is_scratchpad_shown {
+            self.show_scratchpad();
+            self.scratchpad.windows().last().cloned()
+        } else {
+            if let Some(output) = self.scratchpad.output().cloned() {
+                self.scratchpad.unmap_output(&output);
+            }
+            None
+        }
+    }
+
+    // Centers the scratchpad windows on the active output
+    fn show_scratchpad(&mut self) {
+        let Some((output, area)) =
+            self.output.clone().zip(self.output.as_ref().and_then(output_area))
+        else {
+            return;
+        };
+        if let Some(old) = self.scratchpad.output().cloned() {
+            self.scratchpad.unmap_output(&old);
+        }
+        for window in self.scratchpad.windows().cloned().collect::<Vec<_>>() {
+            let size = window.geometry().size;
+            let offset = ((area.size.w - size.w) / 2, (area.size.h - size.h) / 2);
+            self.scratchpad.map_window(window, area.loc + offset.into(), false);
+        }
+        self.scratchpad.map_output(&output);
+    }
+
+    // The shown scratchpad moves along to the output that becomes active
+    fn set_output(&mut self, output: Output) {
+        let is_changed = self.output.as_ref() != Some(&output);
+        self.output = Some(output);
+        if is_changed && self.is_scratchpad_shown {
+            self.show_scratchpad();
+        }
+    }
+
+    // The scratchpad is above the current workspace while shown, followed by the workspaces
+    // of the other outputs
+    fn visible(&self) -> impl Iterator<Item = &Workspace> {
+        let scratchpad = self.is_scratchpad_shown.then_some(&self.scratchpad);
+        let others = self.workspaces.iter().enumerate().filter_map(move |(index, workspace)| {
+            (index != self.current && workspace.output().is_some()).then_some(workspace)
+        });
+        scratchpad.into_iter().chain(std::iter::once(self.current())).chain(others)
+    }
+
+    pub fn visible_windows(&self) -> impl Iterator<Item = &Window> {
+        self.visible().flat_map(Workspace::windows)
+    }
+
+    pub fn window_under(
+        &self,
+        point: Point<f64, Logical>,
+    ) -> Option<(&Window, Point<i32, Logical>)> {
+        self.visible().find_map(|workspace| workspace.window_under(point))
+    }
+
+    pub fn surface_under(
+        &self,
+        point: Point<f64, Logical>,
+    ) -> Option<(WlSurface, Point<i32, Logical>)> {
+        self.visible().find_map(|workspace| workspace.surface_under(point))
+    }
+
+    pub fn raise_window(&mut self, window: &Window, activate: bool) {
+        if let Some(workspace) = self.owner_mut(window) {
+            workspace.raise_window(window, activate);
+        }
+    }
+
+    pub fn fullscreen(&mut self, window: &Window) -> Option<()> {
+        if self.scratchpad.contains(window) {
+            return None;
+        }
+        let output_geometry = self.output_geometry()?;
         GeometryBeforeFullscreen::set(window);
         window.toplevel()?.with_pending_state(|state| {
-            state.size = self.output_geometry().map(|g| g.size);
+            state.size = Some(output_geometry.size);
         });
         IsFullscreen::set(window, true);
+        self.current_mut().map_window(window.to_owned(), output_geometry.loc, true);
         window.toplevel()?.send_pending_configure();
+        self.arrange();
         Some(())
     }
 
@@ -66,13 +366,286 @@ impl Workspaces {
         }
         IsFullscreen::set(window, false);
         window.toplevel()?.send_pending_configure();
+        self.arrange();
         Some(())
     }
 
+    pub fn set_layout(&mut self, kind: LayoutKind) {
+        let tiling = self.tiling.clone();
+        self.update_layout(|layout| layout.set_kind(kind, &tiling));
+    }
+
+    pub fn adjust_master_ratio(&mut self, delta: f32) {
+        self.update_layout(|layout| {
+            if let Layout::MasterStack(layout) = layout {
+                layout.adjust_ratio(delta);
+            }
+        });
+    }
+
+    pub fn adjust_master_count(&mut self, delta: isize) {
+        self.update_layout(|layout| {
+            if let Layout::MasterStack(layout) = layout {
+                layout.adjust_count(delta);
+            }
+        });
+    }
+
+    pub fn focus_column(&mut self, delta: isize) -> Option<Window> {
+        self.update_columns(|columns| columns.focus_column(delta)).flatten()
+    }
+
+    pub fn move_column(&mut self, delta: isize) {
+        self.update_columns(|columns| columns.move_column(delta));
+    }
+
+    pub fn consume_into_column(&mut self) {
+        self.update_columns(Columns::consume_into_column);
+    }
+
+    pub fn expel_from_column(&mut self) {
+        self.update_columns(Columns::expel_from_column);
+    }
+
+    pub fn toggle_split_direction(&mut self) {
+        self.update_layout(|layout| {
+            if let Layout::Dwindle(layout) = layout {
+                layout.toggle_split_direction();
+            }
+        });
+    }
+
+    pub fn split(&mut self, layout: ContainerLayout) {
+        self.update_tree(|tree| tree.split(layout));
+    }
+
+    pub fn set_container_layout(&mut self, layout: ContainerLayout) {
+        self.update_tree(|tree| tree.set_container_layout(layout));
+    }
+
+    pub fn focus_parent(&mut self) {
+        self.update_tree(Tree::focus_parent);
+    }
+
+    pub fn focus_child(&mut self) {
+        self.update_tree(Tree::focus_child);
+    }
+
+    pub fn move_window(&mut self, direction: Direction) {
+        self.update_tree(|tree| tree.move_window(direction));
+    }
+
+    pub fn window_in_direction(&self, window: &Window, direction: Direction) -> Option<Window> {
+        self.current().window_in_direction(window, direction)
+    }
+
+    // Tiled windows trade places in the layout, floating ones their locations
+    pub fn swap_windows(&mut self, a: &Window, b: &Window) {
+        let workspace = self.current_mut();
+        match (IsFloating::get(a), IsFloating::get(b)) {
+            (false, false) => {
+                workspace.layout.swap_windows(a, b);
+                workspace.focus(a);
+            }
+            (true, true) => {
+                let space = &workspace.space;
+                let Some((a_loc, b_loc)) = space.element_location(a).zip(space.element_location(b))
+                else {
+                    return;
+                };
+                workspace.map_window(a.clone(), b_loc, true);
+                workspace.map_window(b.clone(), a_loc, false);
+            }
+            _ => return,
+        }
+        self.arrange();
+    }
+
+    pub fn raise_floating(&mut self, window: &Window) {
+        if IsFloating::get(window) {
+            self.raise_window(window, false);
+        }
+    }
+
+    fn update_tree(&mut self, f: impl FnOnce(&mut Tree)) {
+        self.update_layout(|layout| {
+            if let Layout::Tree(tree) = layout {
+                f(tree);
+            }
+        });
+    }
+
+    fn update_columns<T>(&mut self, f: impl FnOnce(&mut Columns) -> T) -> Option<T> {
+        self.update_layout(|layout| match layout {
+            Layout::Columns(columns) => Some(f(columns)),
+            _ => None,
+        })
+    }
+
+    fn update_layout<T>(&mut self, f: impl FnOnce(&mut Layout) -> T) -> T {
+        let result = f(&mut self.current_mut().layout);
+        self.arrange();
+        result
+    }
+
+    pub fn remove_toplevel(&mut self, surface: &WlSurface) {
+        for index in 0..self.workspaces.len() {
+            if self.workspaces[index].remove_toplevel(surface) {
+                self.arrange_workspace(index);
+            }
+        }
+        self.scratchpad.remove_toplevel(surface);
+        self.prune();
+    }
+
+    // Focusing a window on another output activates that output, returns whether it did
+    pub fn focus(&mut self, window: &Window) -> bool {
+        let shown = self
+            .workspace_of(window)
+            .filter(|&index| index != self.current && self.workspaces[index].output().is_some());
+        let is_switched = shown.is_some_and(|index| self.activate(index));
+        if self.current_mut().focus(window) {
+            self.arrange();
+        }
+        is_switched
+    }
+
+    pub fn arrange(&mut self) {
+        self.arrange_workspace(self.current);
+    }
+
+    // Workspaces not shown on any output are arranged as if they were on the active one
+    fn arrange_workspace(&mut self, index: usize) {
+        let output = self.workspaces[index].output().or(self.output.as_ref());
+        let Some(area) = output.and_then(output_area) else {
+            return;
+        };
+        self.workspaces[index].arrange(area, self.gap);
+    }
+
     pub fn is_fullscreen(&self, window: &Window) -> bool {
         IsFullscreen::get(window)
     }
 
+    // Floating windows keep their geometry and stay above tiled ones
+    pub fn toggle_floating(&mut self, window: &Window) {
+        if self.scratchpad.contains(window) {
+            return;
+        }
+        let is_floating = !IsFloating::get(window);
+        IsFloating::set(window, is_floating);
+        let workspace = self.current_mut();
+        if is_floating {
+            workspace.layout.remove_window(window);
+            workspace.raise_window(window, true);
+        } else {
+            workspace.layout.add_window(window.clone());
+        }
+        self.arrange();
+    }
+
+    // Positions are relative to the output, without one windows of a known size are centered
+    pub fn place_floating(
+        &mut self,
+        window: &Window,
+        size: Option<(i32, i32)>,
+        position: Option<(i32, i32)>,
+    ) -> Option<()> {
+        let area = self.output_geometry()?;
+        let size = size.map(Size::from);
+        if let Some(size) = size {
+            window.toplevel()?.with_pending_state(|state| state.size = Some(size));
+        }
+        let size = size.unwrap_or_else(|| window.geometry().size);
+        let location = match position {
+            Some(position) => Point::from(position),
+            None if size.w > 0 && size.h > 0 => {
+                ((area.size.w - size.w) / 2, (area.size.h - size.h) / 2).into()
+            }
+            None => return Some(()),
+        };
+        self.current_mut().map_window(window.clone(), area.loc + location, true);
+        Some(())
+    }
+
+    pub fn is_floating(&self, window: &Window) -> bool {
+        IsFloating::get(window)
+    }
+
+    pub fn count(&self) -> usize {
+        self.workspaces.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Workspace)> {
+        self.workspaces.iter().enumerate()
+    }
+
+    pub fn workspace_of(&self, window: &Window) -> Option<usize> {
+        self.workspaces.iter().position(|workspace| workspace.contains(window))
+    }
+
+    // The workspace holding the window, or the scratchpad
+    pub fn owner(&self, window: &Window) -> Option<&Workspace> {
+        match self.workspace_of(window) {
+            Some(index) => Some(&self.workspaces[index]),
+            None => self.scratchpad.contains(window).then_some(&self.scratchpad),
+        }
+    }
+
+    pub fn window_location(&self, window: &Window) -> Option<Point<i32, Logical>> {
+        self.owner(window)?.space.element_location(window)
+    }
+
+    pub fn owner_mut(&mut self, window: &Window) -> Option<&mut Workspace> {
+        match self.workspace_of(window) {
+            Some(index) => Some(&mut self.workspaces[index]),
+            None => self.scratchpad.contains(window).then_some(&mut self.scratchpad),
+        }
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = &Output> {
+        self.outputs.iter()
+    }
+
+    pub fn workspace_on(&self, output: &Output) -> Option<usize> {
+        self.workspaces.iter().position(|workspace| workspace.output() == Some(output))
+    }
+
+    pub fn output_under(&self, point: Point<f64, Logical>) -> Option<&Output> {
+        self.outputs
+            .iter()
+            .find(|output| output_area(output).is_some_and(|area| area.to_f64().contains(point)))
+    }
+
+    pub fn output_in_direction(&self, direction: Direction) -> Option<Output> {
+        let active = self.output.as_ref()?;
+        let others = self.outputs.iter().filter(|output| *output != active);
+        let others = others.filter_map(|output| Some((output.clone(), output_area(output)?)));
+        layout::nearest_in_direction(output_area(active)?, others, direction)
+    }
+
+    // Swaps the current workspace with the one shown on `output`, which becomes active
+    pub fn move_workspace_to_output(&mut self, output: &Output) {
+        let Some((active, other)) = self.output.clone().zip(self.workspace_on(output)) else {
+            return;
+        };
+        if active == *output {
+            return;
+        }
+        let current = self.current;
+        self.workspaces[current].unmap_output(&active);
+        self.workspaces[other].unmap_output(output);
+        self.workspaces[current].map_output(output);
+        self.workspaces[other].map_output(&active);
+        self.set_output(output.clone());
+        self.arrange_workspace(current);
+        self.arrange_workspace(other);
+    }
+
     pub fn current(&self) -> &Workspace {
         &self.workspaces[self.current]
     }
@@ -81,112 +654,120 @@ impl Workspaces {
         &mut self.workspaces[self.current]
     }
 
-    pub fn change_output_mode(&self, new_mode: Mode) {
-        if let Some(output) = self.output.as_ref() {
-            output.change_current_state(Some(new_mode), None, None, None);
-        }
+    pub fn change_output_mode(&mut self, output: &Output, new_mode: Mode) {
+        output.change_current_state(Some(new_mode), None, None, None);
+        self.arrange_output(output);
     }
 
-    pub fn _change_output_transform(&self, new_transform: Transform) {
-        if let Some(output) = self.output.as_ref() {
-            output.change_current_state(None, Some(new_transform), None, None);
-        }
+    pub fn change_output_transform(&mut self, output: &Output, new_transform: Transform) {
+        output.change_current_state(None, Some(new_transform), None, None);
+        self.arrange_output(output);
     }
 
-    pub fn _change_output_scale(&self, new_scale: Scale) {
-        if let Some(output) = self.output.as_ref() {
-            output.change_current_state(None, None, Some(new_scale), None);
-        }
+    pub fn change_output_scale(&mut self, output: &Output, new_scale: Scale) {
+        output.change_current_state(None, None, Some(new_scale), None);
+        self.arrange_output(output);
     }
 
-    pub fn _change_output_location(&self, new_location: Point<i32, Logical>) {
-        if let Some(output) = self.output.as_ref() {
-            output.change_current_state(None, None, None, Some(new_location));
+    pub fn change_output_location(&mut self, output: &Output, new_location: Point<i32, Logical>) {
+        output.change_current_state(None, None, None, Some(new_location));
+        self.arrange_output(output);
+    }
+
+    // Also maps the output again, at its new location
+    fn arrange_output(&mut self, output: &Output) {
+        if let Some(index) = self.workspace_on(output) {
+            self.arrange_workspace(index);
         }
     }
 
+    // The first output becomes the active one, the others show a workspace not shown yet
     pub fn map_output(&mut self, output: &Output) {
-        self.output = Some(output.clone());
-        self.current_mut().map_output(output, Point::default());
+        self.outputs.push(output.clone());
+        let index = if self.output.is_none() {
+            self.set_output(output.clone());
+            self.current
+        } else if let Some(index) = self.workspaces.iter().position(|w| w.output().is_none()) {
+            index
+        } else {
+            self.workspaces.push(Workspace::new(&self.tiling, None));
+            self.workspaces.len() - 1
+        };
+        self.workspaces[index].map_output(output);
+        self.arrange_workspace(index);
     }
 
     pub fn output_geometry(&self) -> Option<Rectangle<i32, Logical>> {
         self.output.as_ref().and_then(|output| self.current().output_geometry(output))
     }
 
-    pub fn output_transform(&self) -> Option<Transform> {
-        self.output.as_ref().map(|output| output.current_transform())
-    }
-
     pub fn refresh(&mut self) {
-        self.current_mut().refresh();
+        for index in 0..self.workspaces.len() {
+            if self.workspaces[index].refresh() {
+                self.arrange_workspace(index);
+            }
+        }
+        self.scratchpad.refresh();
+        self.prune();
     }
 
-    pub fn render_elements(
+    pub fn render_elements<B: Backend>(
         &self,
-        backend: &mut impl Backend,
+        backend: &mut B,
+        output: &Output,
         focus: Option<&Window>,
         config: &Config,
-    ) -> Result<Vec<OutputRenderElement>> {
+    ) -> Result<Vec<OutputRenderElement<B::Renderer>>> {
         let mut elements = vec![];
-        let Some(output) = self.output.as_ref() else {
-            return Ok(elements);
-        };
-        let space = &self.current().space;
         let output_scale = output.current_scale().fractional_scale();
-        let output_geometry = self.output_geometry().unwrap();
-        let output_transform = self.output_transform().unwrap();
-        let alpha = 1.0;
-        let scale = 1.0;
+        let output_transform = output.current_transform();
 
-        for window in space.elements_for_output(output).rev() {
-            let mut geometry = space.element_geometry(window).unwrap_or_default();
+        let windows = self.visible().filter(|workspace| workspace.output() == Some(output));
+        let windows = windows.flat_map(|workspace| {
+            let space = &workspace.space;
+            space.elements_for_output(output).rev().map(move |window| (workspace, window))
+        });
+        for (workspace, window) in windows {
+            let space = &workspace.space;
+            let view_offset = workspace.view_offset();
 
-            let size = geometry.size.to_buffer(output_scale as i32, Transform::Normal);
+            let mut geometry = space.element_geometry(window).unwrap_or_default();
+            geometry.loc -= output.current_location() + view_offset;
 
-            if size.w == 0 || size.h == 0 {
+            if geometry.size.w == 0 || geometry.size.h == 0 {
                 continue;
             }
 
-            let window_location = (output_transform
-                .transform_point_in(output_geometry.loc, &output_geometry.size)
-                - output_transform.transform_point_in(geometry.loc, &geometry.size)
-                - Point::from((
-                    output_geometry.loc.x - geometry.loc.x,
-                    geometry.loc.y - output_geometry.loc.y,
-                ))
-                - window.geometry().loc)
-                .to_physical_precise_round(output_scale);
-
             let popups_location =
                 (geometry.loc - window.geometry().loc).to_physical_precise_round(output_scale);
             let surface = window.toplevel().expect("Wayland window").wl_surface();
+            // Popups fade along with their window
+            let style = WindowStyle::get(window);
 
-            let (window_elements, popup_elements) = split_surface_render_elements(
+            elements.extend(popup_render_elements(
                 backend.renderer(),
                 surface,
-                window_location,
                 popups_location,
                 window.geometry(),
-                scale,
-                alpha,
-            );
-
-            elements.extend(popup_elements);
+                output_scale,
+                style.opacity,
+            ));
 
-            if let Some(texture) = backend.render_offscreen(&window_elements, size)? {
+            if let Some(texture) = self.window_texture(backend, output, window)? {
                 let color = focus
                     .and_then(|focus| focus.eq(window).then_some(config.outline.focused_color))
+                    .or(style.outline_color)
                     .unwrap_or(config.outline.color);
                 let radius = config.outline.radius as f32;
                 let thickness = config.outline.thickness as f32;
 
-                let program = OutlineShader::program(backend.renderer());
+                let program = backend.renderer().outline_program();
                 let t = thickness as i32;
                 geometry.size += (t * 2, t * 2).into();
                 geometry.loc -= (t, t).into();
                 let element = RoundedElement::new(
                     color,
+                    style.opacity,
                     geometry,
                     program,
                     radius,
@@ -194,31 +775,92 @@ impl Workspaces {
                     output_transform,
                     thickness,
                 );
+                let element = element.with_buffer_scale(output_scale);
                 elements.push(OutputRenderElement::RoundedWindow(element));
             }
         }
+
+        // Tabs go below the windows, the active ones in the focused outline color
+        for workspace in self.visible().filter(|workspace| workspace.output() == Some(output)) {
+            let offset = output.current_location() + workspace.view_offset();
+            for &(tab, is_active) in workspace.tabs() {
+                let mut tab = layout::shrink(tab, self.gap);
+                tab.loc -= offset;
+                let [r, g, b] =
+                    if is_active { config.outline.focused_color } else { config.outline.color };
+                elements.push(solid(tab, [r, g, b, 1.0], output_scale));
+            }
+        }
         Ok(elements)
     }
 
+    // Renders the window without its popups into a texture oriented like the output
+    pub fn window_texture<B: Backend>(
+        &self,
+        backend: &mut B,
+        output: &Output,
+        window: &Window,
+    ) -> Result<Option<<B::Renderer as Renderer>::TextureId>> {
+        let Some(output_geometry) = output_area(output) else {
+            return Ok(None);
+        };
+        let output_scale = output.current_scale().fractional_scale();
+        let output_transform = output.current_transform();
+        let geometry = window.geometry();
+        let size = geometry.size.to_f64().to_buffer(output_scale, Transform::Normal).to_i32_ceil();
+
+        // Location inside of the offscreen texture, which does not depend on the window position
+        let location = (output_transform
+            .transform_point_in(Point::default(), &output_geometry.size)
+            - output_transform.transform_point_in(Point::default(), &geometry.size)
+            - geometry.loc)
+            .to_physical_precise_round(output_scale);
+
+        let surface = window.toplevel().expect("Wayland window").wl_surface();
+        let elements: Vec<OutputRenderElement<B::Renderer>> =
+            surface::render_elements_from_surface_tree(
+                backend.renderer(),
+                surface,
+                location,
+                output_scale,
+                1.0,
+                Kind::Unspecified,
+            );
+
+        // Only windows with a new commit on any of their surfaces are rendered again
+        let commits = elements.iter().map(|e| (e.id().clone(), e.current_commit())).collect();
+        let key = TextureKey { commits, size, location };
+        if let Some(texture) = WindowTexture::get(window, &key) {
+            return Ok(Some(texture));
+        }
+        let texture = backend.render_offscreen(&elements, size)?;
+        if let Some(texture) = texture.as_ref() {
+            WindowTexture::set(window, key, texture.clone());
+        }
+        Ok(texture)
+    }
+
     pub fn send_frames(&self, time: Duration) {
-        if let Some(output) = self.output.as_ref() {
-            self.current().windows().for_each(|w| {
+        for workspace in self.visible() {
+            let Some(output) = workspace.output() else {
+                continue;
+            };
+            workspace.windows().for_each(|w| {
                 w.send_frame(output, time, Some(Duration::ZERO), |_, _| None);
-            })
+            });
         }
     }
 }
 
-fn split_surface_render_elements(
-    renderer: &mut GlesRenderer,
+fn popup_render_elements<R: OutlineRenderer>(
+    renderer: &mut R,
     surface: &WlSurface,
-    window_location: Point<i32, Physical>,
     popups_location: Point<i32, Physical>,
     geometry: Rectangle<i32, Logical>,
     scale: f64,
     alpha: f32,
-) -> (Vec<OutputRenderElement>, Vec<OutputRenderElement>) {
-    let popup_render_elements = PopupManager::popups_for_surface(surface)
+) -> Vec<OutputRenderElement<R>> {
+    PopupManager::popups_for_surface(surface)
         .flat_map(|(popup, popup_offset)| {
             let offset = (geometry.loc + popup_offset - popup.geometry().loc)
                 .to_physical_precise_round(scale);
@@ -232,16 +874,122 @@ fn split_surface_render_elements(
                 Kind::Unspecified,
             )
         })
-        .collect();
-
-    let window_render_elements = surface::render_elements_from_surface_tree(
-        renderer,
-        surface,
-        window_location,
-        scale,
-        alpha,
-        Kind::Unspecified,
-    );
-
-    (window_render_elements, popup_render_elements)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use smithay::output::{PhysicalProperties, Subpixel};
+
+    use super::*;
+
+    fn output(name: &str, x: i32) -> Output {
+        let properties = PhysicalProperties {
+            size: (0, 0).into(),
+            subpixel: Subpixel::Unknown,
+            make: "Test".into(),
+            model: "Test".into(),
+        };
+        let output = Output::new(name.into(), properties);
+        let mode = Mode { size: (1920, 1080).into(), refresh: 60_000 };
+        output.change_current_state(Some(mode), None, None, Some((x, 0).into()));
+        output
+    }
+
+    #[test]
+    fn dynamic_workspaces() {
+        let config = Config { workspace_count: 2, ..Config::default() };
+        let mut workspaces = Workspaces::new(&config);
+
+        assert!(workspaces.find_or_create(&WorkspaceId::Index(4)).is_err());
+        let web = workspaces.find_or_create(&WorkspaceId::Name("web".into())).unwrap();
+        assert_eq!(web, 2);
+        assert_eq!(workspaces.find_or_create(&WorkspaceId::Name("web".into())).unwrap(), web);
+        workspaces.switch_to(web).unwrap();
+        let next = workspaces.find_or_create(&WorkspaceId::Index(4)).unwrap();
+        assert_eq!(next, 3);
+
+        // Switching away leaves both empty workspaces behind, and with them the previous one
+        assert!(workspaces.switch_to(1).unwrap());
+        assert_eq!(workspaces.count(), 2);
+        assert_eq!(workspaces.previous_index(), None);
+        assert!(workspaces.switch_to(2).is_err());
+        assert!(!workspaces.switch_to(1).unwrap());
+    }
+
+    #[test]
+    fn relative_workspaces() {
+        let config = Config { workspace_count: 3, ..Config::default() };
+        let mut workspaces = Workspaces::new(&config);
+        let wrap = WorkspaceStep { wrap: true, skip_empty: false };
+        let skip_empty = WorkspaceStep { wrap: true, skip_empty: true };
+
+        assert_eq!(workspaces.relative(-1, WorkspaceStep::default()), None);
+        assert_eq!(workspaces.relative(-1, wrap), Some(2));
+        assert_eq!(workspaces.relative(1, skip_empty), None);
+
+        assert_eq!(workspaces.previous_index(), None);
+        workspaces.switch_to(2).unwrap();
+        assert_eq!(workspaces.relative(1, WorkspaceStep::default()), None);
+        assert_eq!(workspaces.relative(1, wrap), Some(0));
+        assert_eq!(workspaces.previous_index(), Some(0));
+    }
+
+    #[test]
+    fn reconfigure_workspaces() {
+        let config = Config { workspace_count: 3, ..Config::default() };
+        let mut workspaces = Workspaces::new(&config);
+        workspaces.switch_to(2).unwrap();
+
+        // The current workspace outlives the ones no longer configured
+        let names = vec!["main".to_string()];
+        workspaces.reconfigure(&Config { workspace_count: 1, workspace_names: names, ..config });
+        assert_eq!(workspaces.count(), 2);
+        assert_eq!(workspaces.current_index(), 1);
+        assert_eq!(workspaces.workspaces[0].name.as_deref(), Some("main"));
+
+        workspaces.reconfigure(&Config { workspace_count: 4, ..Config::default() });
+        assert_eq!(workspaces.count(), 4);
+        assert_eq!(workspaces.workspaces[0].name, None);
+    }
+
+    #[test]
+    fn workspaces_per_output() {
+        let config = Config { workspace_count: 2, ..Config::default() };
+        let mut workspaces = Workspaces::new(&config);
+        let (left, right) = (output("left", 0), output("right", 1920));
+        workspaces.map_output(&left);
+        workspaces.map_output(&right);
+        assert_eq!(workspaces.workspace_on(&left), Some(0));
+        assert_eq!(workspaces.workspace_on(&right), Some(1));
+        assert_eq!(workspaces.output_in_direction(Direction::Right), Some(right.clone()));
+        assert_eq!(workspaces.output_in_direction(Direction::Left), None);
+
+        // The workspace of another output is switched to by activating that output
+        workspaces.switch_to(1).unwrap();
+        assert_eq!(workspaces.output.as_ref(), Some(&right));
+        assert_eq!(workspaces.workspace_on(&left), Some(0));
+
+        workspaces.move_workspace_to_output(&left);
+        assert_eq!(workspaces.workspace_on(&left), Some(1));
+        assert_eq!(workspaces.workspace_on(&right), Some(0));
+        assert_eq!(workspaces.output.as_ref(), Some(&left));
+        assert_eq!(workspaces.current_index(), 1);
+    }
+
+    #[test]
+    fn scratchpad_follows_active_output() {
+        let config = Config { workspace_count: 2, ..Config::default() };
+        let mut workspaces = Workspaces::new(&config);
+        let (left, right) = (output("left", 0), output("right", 1920));
+        workspaces.map_output(&left);
+        workspaces.map_output(&right);
+
+        workspaces.toggle_scratchpad();
+        assert_eq!(workspaces.scratchpad.output(), Some(&left));
+        workspaces.switch_to(1).unwrap();
+        assert_eq!(workspaces.scratchpad.output(), Some(&right));
+        workspaces.move_workspace_to_output(&left);
+        assert_eq!(workspaces.scratchpad.output(), Some(&left));
+    }
 }
@@ -1,14 +1,35 @@
 use smithay::desktop::{Space, Window, WindowSurfaceType};
 use smithay::output::Output;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::utils::{Logical, Point, Rectangle};
+use smithay::utils::{IsAlive, Logical, Point, Rectangle};
+
+use super::layout::{self, Layout};
+use crate::config::{Direction, Tiling};
+use crate::shell::floating::IsFloating;
+use crate::shell::fullscreen::IsFullscreen;
 
-#[derive(Default)]
 pub struct Workspace {
+    pub name: Option<String>,
     pub space: Space<Window>,
+    pub layout: Layout,
+    // Location of the output area the workspace was last arranged for
+    origin: Point<i32, Logical>,
+    // Windows the layout hides, like inactive tabs, which are not in the space
+    hidden: Vec<Window>,
 }
 
 impl Workspace {
+    pub fn new(tiling: &Tiling, name: Option<String>) -> Self {
+        let layout = Layout::new(tiling.layout, tiling);
+        let origin = Point::default();
+        Self { name, space: Space::default(), layout, origin, hidden: Vec::new() }
+    }
+
+    pub fn add_window(&mut self, window: Window, activate: bool) {
+        self.layout.add_window(window.clone());
+        self.space.map_element(window, Point::default(), activate);
+    }
+
     pub fn map_window(
         &mut self,
         window: Window,
@@ -19,18 +40,103 @@ impl Workspace {
     }
 
     pub fn unmap_window(&mut self, window: &Window) {
+        self.layout.remove_window(window);
+        self.hidden.retain(|w| w != window);
         self.space.unmap_elem(window);
     }
 
+    // Windows are placed in workspace coordinates, the output is mapped at the view offset
+    pub fn arrange(&mut self, area: Rectangle<i32, Logical>, gap: i32) {
+        let geometries = self.layout.arrange(area);
+        // Layouts leave out windows they hide, like inactive tabs
+        self.hidden = self.layout.windows();
+        self.hidden.retain(|window| {
+            !IsFullscreen::get(window) && !geometries.iter().any(|(w, _)| w == window)
+        });
+        for window in self.hidden.iter() {
+            self.space.unmap_elem(window);
+        }
+        for (window, geometry) in geometries {
+            let geometry = layout::shrink(geometry, gap);
+            layout::configure(&window, geometry.size);
+            self.space.map_element(window, geometry.loc, false);
+        }
+        // Mapping raises windows, keep floating and then fullscreen ones on top,
+        // the latter also in view. Floating windows move along to another output
+        let offset = area.loc - self.origin;
+        self.origin = area.loc;
+        let floating = self.space.elements().filter(|w| IsFloating::get(w)).cloned();
+        for window in floating.collect::<Vec<_>>() {
+            let location = self.space.element_location(&window).unwrap_or_default();
+            self.space.map_element(window, location + offset, false);
+        }
+        let fullscreen = self.space.elements().filter(|w| IsFullscreen::get(w)).cloned();
+        for window in fullscreen.collect::<Vec<_>>() {
+            self.space.map_element(window, area.loc + self.view_offset(), false);
+        }
+        if let Some(output) = self.output().cloned() {
+            self.map_output(&output);
+        }
+    }
+
+    pub fn remove_toplevel(&mut self, surface: &WlSurface) -> bool {
+        let mut windows = self.layout.windows().into_iter().chain(self.space.elements().cloned());
+        let window = windows.find(|window| {
+            window.toplevel().is_some_and(|toplevel| toplevel.wl_surface() == surface)
+        });
+        if let Some(window) = window.as_ref() {
+            self.unmap_window(window);
+        }
+        window.is_some()
+    }
+
+    pub fn focus(&mut self, window: &Window) -> bool {
+        self.layout.focus(window)
+    }
+
+    pub fn view_offset(&self) -> Point<i32, Logical> {
+        self.layout.view_offset()
+    }
+
+    pub fn origin(&self) -> Point<i32, Logical> {
+        self.origin
+    }
+
+    pub fn tabs(&self) -> &[(Rectangle<i32, Logical>, bool)] {
+        self.layout.tabs()
+    }
+
+    pub fn contains(&self, window: &Window) -> bool {
+        self.windows().any(|w| w == window)
+    }
+
+    // Mapped windows from bottom to top, then the ones the layout hides
     pub fn windows(&self) -> impl DoubleEndedIterator<Item = &Window> {
-        self.space.elements()
+        self.space.elements().chain(self.hidden.iter())
+    }
+
+    // Layouts that hide windows may have a tab in that direction, otherwise the nearest
+    // mapped window is picked
+    pub fn window_in_direction(&self, window: &Window, direction: Direction) -> Option<Window> {
+        if let Some(tab) = self.layout.tab_in_direction(direction) {
+            return Some(tab);
+        }
+        let geometry = self.space.element_geometry(window)?;
+        let others =
+            self.space.elements().filter(|w| *w != window).filter_map(|w| {
+                self.space.element_geometry(w).map(|geometry| (w.clone(), geometry))
+            });
+        layout::nearest_in_direction(geometry, others, direction)
     }
 
     pub fn window_under(
         &self,
         point: impl Into<Point<f64, Logical>>,
     ) -> Option<(&Window, Point<i32, Logical>)> {
-        self.space.element_under(point)
+        let offset = self.view_offset();
+        self.space
+            .element_under(point.into() + offset.to_f64())
+            .map(|(window, location)| (window, location - offset))
     }
 
     pub fn surface_under(
@@ -48,19 +154,29 @@ impl Workspace {
         self.space.raise_element(window, activate);
     }
 
-    pub fn map_output<P: Into<Point<i32, Logical>>>(&mut self, output: &Output, location: P) {
-        self.space.map_output(output, location);
+    pub fn map_output(&mut self, output: &Output) {
+        self.space.map_output(output, output.current_location() + self.view_offset());
     }
 
     pub fn unmap_output(&mut self, output: &Output) {
         self.space.unmap_output(output);
     }
 
+    // The output the workspace is shown on, if any
+    pub fn output(&self) -> Option<&Output> {
+        self.space.outputs().next()
+    }
+
     pub fn output_geometry(&self, output: &Output) -> Option<Rectangle<i32, Logical>> {
         self.space.output_geometry(output)
     }
 
-    pub fn refresh(&mut self) {
+    // Returns whether any window is gone and the workspace should be rearranged
+    pub fn refresh(&mut self) -> bool {
         self.space.refresh();
+        let dead = self.layout.windows().into_iter().filter(|w| !w.alive()).collect::<Vec<_>>();
+        dead.iter().for_each(|window| self.layout.remove_window(window));
+        self.hidden.retain(|w| w.alive());
+        !dead.is_empty()
     }
 }
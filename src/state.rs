@@ -1,6 +1,7 @@
 use std::time::Instant;
 
-use smithay::desktop::PopupManager;
+use anyhow::Result;
+use smithay::desktop::{PopupManager, Window};
 use smithay::input::{Seat, SeatState};
 use smithay::reexports::calloop::LoopHandle;
 use smithay::reexports::wayland_server::backend::{ClientData, ClientId, DisconnectReason};
@@ -10,9 +11,12 @@ use smithay::wayland::selection::data_device::DataDeviceState;
 use smithay::wayland::shell::xdg::decoration::XdgDecorationState;
 use smithay::wayland::shell::xdg::XdgShellState;
 use smithay::wayland::shm::ShmState;
+use tracing::{error, info, warn};
 
 use crate::backend::BackendState;
-use crate::config::Config;
+use crate::config::{Config, Spawn};
+use crate::ipc::message::{Event, WindowInfo};
+use crate::ipc::{query, Subscribers};
 use crate::shell::Shell;
 use crate::PKG_NAME;
 
@@ -31,6 +35,7 @@ pub struct State {
     pub config: Config,
     pub popups: PopupManager,
     pub shell: Shell,
+    pub subscribers: Subscribers,
 
     pub compositor_state: CompositorState,
     pub xdg_shell_state: XdgShellState,
@@ -43,15 +48,19 @@ pub struct State {
 }
 
 impl State {
-    pub fn new(dh: &DisplayHandle, event_loop: LoopHandle<'static, CalloopData>) -> Self {
+    pub fn new(
+        dh: &DisplayHandle,
+        event_loop: LoopHandle<'static, CalloopData>,
+        config: Config,
+    ) -> Self {
         let is_running = true;
         let start_time = Instant::now();
 
         let display_handle = dh.clone();
 
-        let config = Config::new().unwrap();
         let popups = PopupManager::default();
-        let shell = Shell::new(config.workspace_count);
+        let subscribers = Subscribers::default();
+        let shell = Shell::new(&config, event_loop.clone());
 
         let compositor_state = CompositorState::new::<State>(dh);
         let xdg_shell_state = XdgShellState::new::<State>(dh);
@@ -64,7 +73,9 @@ impl State {
 
         Config::setup_watcher(config.path.as_path(), event_loop.clone());
 
-        let _ = seat.add_keyboard(Default::default(), 180, 60);
+        let keyboard = &config.keyboard;
+        let _ =
+            seat.add_keyboard(keyboard.xkb_config(), keyboard.repeat_delay, keyboard.repeat_rate);
         let _ = seat.add_pointer();
 
         Self {
@@ -77,6 +88,7 @@ impl State {
             config,
             popups,
             shell,
+            subscribers,
 
             compositor_state,
             xdg_shell_state,
@@ -90,6 +102,84 @@ impl State {
     }
 }
 
+impl State {
+    pub fn emit(&mut self, event: impl FnOnce(&Self) -> Option<Event>) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        if let Some(event) = event(self) {
+            self.subscribers.emit(&event);
+        }
+    }
+
+    // Read after the switch, as switching prunes workspaces and shifts their indices
+    pub fn emit_workspace_switched(&mut self) {
+        self.emit(|state| {
+            let workspaces = &state.shell.workspaces;
+            let from = workspaces.previous_index().map(|from| from + 1);
+            Some(Event::WorkspaceSwitched { from, to: workspaces.current_index() + 1 })
+        });
+    }
+
+    pub fn emit_window(&mut self, window: &Window, event: fn(WindowInfo) -> Event) {
+        self.emit(|state| query::window(state, window).map(event));
+    }
+
+    pub fn autostart(&mut self, is_reload: bool) {
+        let autostart = (!is_reload).then_some(&self.config.autostart).into_iter().flatten();
+        let commands = autostart.chain(&self.config.exec_always).cloned().collect::<Vec<_>>();
+        for command in commands {
+            info!(?command, "Autostarting");
+            if let Err(err) = self.shell.spawn(command) {
+                error!(?err, "Failed to autostart");
+            }
+        }
+    }
+
+    // A configuration that fails to load is reported and the current one is kept
+    pub fn reload_config(&mut self) {
+        let config = match self.config.reload() {
+            Ok(config) => config,
+            Err(err) => {
+                error!(?err, "Failed to reload configuration");
+                let error = err.to_string();
+                self.emit(|_| Some(Event::ConfigFailed { error }));
+                return;
+            }
+        };
+        let old = std::mem::replace(&mut self.config, config);
+
+        self.shell.workspaces.reconfigure(&self.config);
+        self.configure_outputs();
+        if self.config.keyboard != old.keyboard {
+            self.configure_keyboard();
+        }
+
+        self.autostart(true);
+        self.emit(|_| Some(Event::ConfigReloaded));
+    }
+
+    fn configure_keyboard(&mut self) {
+        let Some(keyboard) = self.seat.get_keyboard() else {
+            return;
+        };
+        let config = self.config.keyboard.clone();
+        keyboard.change_repeat_info(config.repeat_rate, config.repeat_delay);
+        if let Err(err) = keyboard.set_xkb_config(self, config.xkb_config()) {
+            warn!(?err, "Failed to apply keyboard layout");
+        }
+    }
+
+    pub fn spawn_session(&mut self, command: String) -> Result<()> {
+        info!(?command, "Starting session");
+        self.shell.spawn_with(Spawn::Shell(command), |state, status| {
+            info!(?status, "Session command exited");
+            state.is_running = false;
+        })?;
+        Ok(())
+    }
+}
+
 #[derive(Default)]
 pub struct ClientState {
     pub compositor_state: CompositorClientState,